@@ -0,0 +1,73 @@
+use futures::future::{self, BoxFuture};
+use uuid::Uuid;
+
+use crate::Central;
+
+pub type AgentFuture<T> = BoxFuture<'static, Result<T, AgentError>>;
+
+/// The input and output capabilities announced to the remote device, which decide the pairing
+/// method (Just Works, Passkey Entry or Numeric Comparison)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IoCapability {
+    DisplayOnly,
+    DisplayYesNo,
+    KeyboardOnly,
+    NoInputNoOutput,
+    KeyboardDisplay,
+}
+
+impl From<IoCapability> for &'static str {
+    fn from(io_capability: IoCapability) -> &'static str {
+        match io_capability {
+            IoCapability::DisplayOnly => "DisplayOnly",
+            IoCapability::DisplayYesNo => "DisplayYesNo",
+            IoCapability::KeyboardOnly => "KeyboardOnly",
+            IoCapability::NoInputNoOutput => "NoInputNoOutput",
+            IoCapability::KeyboardDisplay => "KeyboardDisplay",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AgentError {
+    Rejected,
+    Canceled,
+}
+
+/// Callbacks for taking part in pairing. Requests which need user input are rejected unless they
+/// are implemented, while display requests are accepted.
+pub trait AgentHandler: Send + Sync + 'static {
+    fn request_pin_code(&self, _central: Central) -> AgentFuture<String> {
+        Box::pin(future::err(AgentError::Rejected))
+    }
+
+    fn display_pin_code(&self, _central: Central, _pin_code: String) -> AgentFuture<()> {
+        Box::pin(future::ok(()))
+    }
+
+    fn request_passkey(&self, _central: Central) -> AgentFuture<u32> {
+        Box::pin(future::err(AgentError::Rejected))
+    }
+
+    fn display_passkey(&self, _central: Central, _passkey: u32, _entered: u16) -> AgentFuture<()> {
+        Box::pin(future::ok(()))
+    }
+
+    fn request_confirmation(&self, _central: Central, _passkey: u32) -> AgentFuture<()> {
+        Box::pin(future::err(AgentError::Rejected))
+    }
+
+    fn request_authorization(&self, _central: Central) -> AgentFuture<()> {
+        Box::pin(future::err(AgentError::Rejected))
+    }
+
+    fn authorize_service(&self, _central: Central, _uuid: Uuid) -> AgentFuture<()> {
+        Box::pin(future::err(AgentError::Rejected))
+    }
+
+    /// The pairing request in progress was canceled by the remote device or a timeout
+    fn cancel(&self) {}
+
+    /// The agent was unregistered by BlueZ and won't receive any more requests
+    fn release(&self) {}
+}
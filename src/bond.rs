@@ -0,0 +1,12 @@
+use crate::Central;
+
+/// A central known to the adapter through pairing
+#[derive(Debug, Clone)]
+pub struct Bond {
+    pub central: Central,
+    pub paired: bool,
+    /// Whether the keys exchanged while pairing were stored, so the bond survives reconnecting
+    pub bonded: bool,
+    pub trusted: bool,
+    pub connected: bool,
+}
@@ -0,0 +1,18 @@
+/// A remote device (central) connected to the peripheral
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Central {
+    pub(crate) address: String,
+}
+
+impl Central {
+    pub fn new<T: Into<String>>(address: T) -> Self {
+        Central {
+            address: address.into(),
+        }
+    }
+
+    /// The address (BlueZ) or identifier (CoreBluetooth) of the central
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+}
@@ -1,10 +1,13 @@
 use std::{error, fmt};
 
+use crate::gatt::Problem;
+
 #[derive(Debug, Clone)]
 pub enum ErrorType {
     Bluez,
     CoreBluetooth,
     Usb,
+    Validation,
 }
 
 impl From<ErrorType> for &'static str {
@@ -13,6 +16,7 @@ impl From<ErrorType> for &'static str {
             ErrorType::Bluez => "Bluez",
             ErrorType::CoreBluetooth => "CoreBluetooth",
             ErrorType::Usb => "USB",
+            ErrorType::Validation => "Validation",
         }
     }
 }
@@ -32,6 +36,7 @@ pub struct Error {
     description: String,
     combined_description: String,
     error_type: ErrorType,
+    problems: Vec<Problem>,
 }
 
 impl Error {
@@ -44,8 +49,26 @@ impl Error {
             description,
             combined_description,
             error_type,
+            problems: vec![],
         }
     }
+
+    pub(crate) fn invalid(problems: Vec<Problem>) -> Self {
+        let description = problems
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("; ");
+        Error {
+            problems,
+            ..Error::new("Invalid service".into(), description, ErrorType::Validation)
+        }
+    }
+
+    /// Every problem found by validating a service, empty for other errors
+    pub fn problems(&self) -> &[Problem] {
+        &self.problems
+    }
 }
 
 impl fmt::Display for Error {
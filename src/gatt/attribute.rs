@@ -0,0 +1,40 @@
+use std::{
+    fmt,
+    sync::atomic::{AtomicU64, Ordering},
+};
+use uuid::Uuid;
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Identifies a characteristic or descriptor, assigned when it's created and kept by its clones
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AttributeId(u64);
+
+impl AttributeId {
+    pub(crate) fn next() -> Self {
+        AttributeId(NEXT_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+impl fmt::Display for AttributeId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// The characteristic or descriptor an event was sent for
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Attribute {
+    pub id: AttributeId,
+    pub uuid: Uuid,
+    /// The UUID of the service the attribute belongs to
+    pub service: Uuid,
+    /// The characteristic a descriptor belongs to, `None` for characteristics
+    pub characteristic: Option<AttributeId>,
+}
+
+impl fmt::Display for Attribute {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ({})", self.uuid, self.id)
+    }
+}
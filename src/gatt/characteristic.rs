@@ -1,34 +1,220 @@
-use super::{descriptor::Descriptor, event::EventSender};
+use super::{
+    descriptor::Descriptor, event::EventSender, AttributeId, NotificationQueue, ResponseDeadline,
+    StoredValue, Subscribers,
+};
 use std::{
-    collections::HashSet,
     hash::{Hash, Hasher},
+    sync::Arc,
+    time::Duration,
 };
+use tokio::sync::watch;
 use uuid::Uuid;
 
+// The ATT transaction timeout
+const INDICATION_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How changes of a value bound to a watch channel reach the subscribed centrals
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueChanges {
+    Notify,
+    Indicate,
+}
+
 #[derive(Debug, Clone)]
 pub struct Characteristic {
+    pub(crate) id: AttributeId,
     pub(crate) uuid: Uuid,
     pub(crate) properties: Properties,
-    pub(crate) value: Option<Vec<u8>>,
-    pub(crate) descriptors: HashSet<Descriptor>,
+    pub(crate) value: StoredValue,
+    pub(crate) value_changes: Option<ValueChanges>,
+    pub(crate) value_writes: Option<Arc<watch::Sender<Vec<u8>>>>,
+    pub(crate) authorize: bool,
+    pub(crate) response_deadline: Option<ResponseDeadline>,
+    pub(crate) reliable_write: bool,
+    pub(crate) writable_auxiliaries: bool,
+    pub(crate) long_read_cache: bool,
+    pub(crate) long_write_assembly: bool,
+    pub(crate) acquire_write: bool,
+    pub(crate) acquire_notify: bool,
+    pub(crate) indication_timeout: Duration,
+    pub(crate) subscribers: Subscribers,
+    pub(crate) notification_queue: NotificationQueue,
+    pub(crate) handle: Option<u16>,
+    pub(crate) descriptors: Vec<Descriptor>,
 }
 
 impl Characteristic {
-    pub fn new(
+    /// Descriptors are registered in the order they're given, which decides their handles
+    pub fn new<I: IntoIterator<Item = Descriptor>>(
         uuid: Uuid,
         properties: Properties,
         value: Option<Vec<u8>>,
-        descriptors: HashSet<Descriptor>,
+        descriptors: I,
     ) -> Self {
         Characteristic {
+            id: AttributeId::next(),
             uuid,
             properties,
-            value,
+            value: StoredValue::new(value),
+            value_changes: None,
+            value_writes: None,
+            authorize: false,
+            response_deadline: None,
+            reliable_write: false,
+            writable_auxiliaries: false,
+            long_read_cache: false,
+            long_write_assembly: false,
+            acquire_write: false,
+            acquire_notify: false,
+            indication_timeout: INDICATION_TIMEOUT,
+            subscribers: Subscribers::default(),
+            notification_queue: NotificationQueue::default(),
+            handle: None,
+            descriptors: descriptors.into_iter().collect(),
+        }
+    }
+
+    /// A characteristic answering reads with the latest value sent on the channel of `value`
+    /// without a handler, and sending every change to subscribed centrals as `changes`. Not
+    /// supported on CoreBluetooth yet.
+    pub fn from_watch<I: IntoIterator<Item = Descriptor>>(
+        uuid: Uuid,
+        value: watch::Receiver<Vec<u8>>,
+        changes: Option<ValueChanges>,
+        descriptors: I,
+    ) -> Self {
+        let mut characteristic = Characteristic::new(
+            uuid,
+            Properties::new(None, None, None, None),
+            None,
             descriptors,
+        );
+        characteristic.value = StoredValue::watch(value);
+        characteristic.value_changes = changes;
+        characteristic
+    }
+
+    /// Tells events for this attribute apart from those of others sharing the handler
+    pub fn id(&self) -> AttributeId {
+        self.id
+    }
+
+    /// Sends the values written by centrals on the channel of `sender` when there's no write
+    /// handler. A write at an offset keeps the current value up to there. Not supported on
+    /// CoreBluetooth yet.
+    pub fn with_write_sender(mut self, sender: watch::Sender<Vec<u8>>) -> Self {
+        self.value_writes = Some(Arc::new(sender));
+        self
+    }
+
+    /// Asks BlueZ to place the characteristic at `handle`, so it keeps it across restarts. Registering
+    /// the application fails when it can't.
+    pub fn with_handle(mut self, handle: u16) -> Self {
+        self.handle = Some(handle);
+        self
+    }
+
+    /// Asks the handler for an `Authorization` with the requesting central before every read
+    /// and write, including those of a stored value. Without a read handler, the write handler
+    /// is asked.
+    pub fn with_authorize(mut self, authorize: bool) -> Self {
+        self.authorize = authorize;
+        self
+    }
+
+    /// Overrides the deadline set on the peripheral for requests to this attribute
+    pub fn with_response_deadline(mut self, response_deadline: ResponseDeadline) -> Self {
+        self.response_deadline = Some(response_deadline);
+        self
+    }
+
+    /// Lets centrals write the value through a queue of prepared writes which is only applied
+    /// once executed
+    pub fn with_reliable_write(mut self, reliable_write: bool) -> Self {
+        self.reliable_write = reliable_write;
+        self
+    }
+
+    /// Lets centrals write the Characteristic User Description descriptor
+    pub fn with_writable_auxiliaries(mut self, writable_auxiliaries: bool) -> Self {
+        self.writable_auxiliaries = writable_auxiliaries;
+        self
+    }
+
+    /// Only asks the read handler for the whole value at offset 0, and serves the parts of a
+    /// long read from it, so they're consistent and the handler doesn't need to slice
+    pub fn with_long_read_cache(mut self, long_read_cache: bool) -> Self {
+        self.long_read_cache = long_read_cache;
+        self
+    }
+
+    /// Collects the parts of long and reliable writes, so the handler receives `WriteRequest`s
+    /// holding the value at offset 0. Parts that don't continue the value are answered with
+    /// `Response::InvalidOffset`, values longer than 512 bytes with
+    /// `Response::InvalidAttributeLength`.
+    ///
+    /// BlueZ hands over the queued parts one at a time once the central executes them, without
+    /// telling which one is the last, so every part writes the value so far and the handler's
+    /// response to it reaches the central. Only the last `WriteRequest` of a long write holds
+    /// the whole value.
+    pub fn with_long_write_assembly(mut self, long_write_assembly: bool) -> Self {
+        self.long_write_assembly = long_write_assembly;
+        self
+    }
+
+    /// Lets BlueZ pass writes without response through a socket, announced to the write
+    /// handler with `Event::WriteAcquired`. Requires `Write::WithoutResponse` or
+    /// `Write::WithAndWithoutResponse`.
+    pub fn with_acquire_write(mut self, acquire_write: bool) -> Self {
+        self.acquire_write = acquire_write;
+        self
+    }
+
+    /// Lets BlueZ take notifications through a socket instead of D-Bus signals, which keeps up
+    /// with a lot more data. Signals are still used whenever the socket isn't acquired.
+    pub fn with_acquire_notify(mut self, acquire_notify: bool) -> Self {
+        self.acquire_notify = acquire_notify;
+        self
+    }
+
+    /// How long `Indicator::indicate` waits for a confirmation, 30 seconds by default
+    pub fn with_indication_timeout(mut self, indication_timeout: Duration) -> Self {
+        self.indication_timeout = indication_timeout;
+        self
+    }
+
+    /// How notifications and indications wait to be sent, one at a time by default
+    pub fn with_notification_queue(mut self, notification_queue: NotificationQueue) -> Self {
+        self.notification_queue = notification_queue;
+        self
+    }
+
+    /// The subscriptions to notifications, shared with every clone of the characteristic
+    pub fn subscribers(&self) -> Subscribers {
+        self.subscribers.clone()
+    }
+
+    /// The value reads are answered with when there's no read handler
+    pub fn value(&self) -> Option<Vec<u8>> {
+        self.value.get()
+    }
+
+    /// Replaces the value shared with every clone of the characteristic, optionally notifying
+    /// the subscribers of the new value. Returns how many subscribers were notified. A value
+    /// bound to a watch channel stops following it.
+    ///
+    /// Only notification subscriptions are notified, which take a `notify` handler or
+    /// `ValueChanges::Notify`, and indications aren't sent. To notify or indicate without a
+    /// handler, send the values on the channel of a `from_watch` characteristic instead.
+    pub async fn set_value(&self, value: Vec<u8>, notify: bool) -> usize {
+        self.value.set(value.clone());
+        if !notify {
+            return 0;
         }
+        self.subscribers.notify(value).await
     }
 }
 
-impl_uuid_hash_eq!(Characteristic);
+impl_id_hash_eq!(Characteristic);
 
 properties!(WriteWithAndWithoutResponse, EventSender, { notify: EventSender, indicate: EventSender });
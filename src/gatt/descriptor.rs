@@ -1,24 +1,86 @@
-use super::event::EventSender;
+use super::{event::EventSender, AttributeId, ResponseDeadline, StoredValue};
 use std::hash::{Hash, Hasher};
 use uuid::Uuid;
 
 #[derive(Debug, Clone)]
 pub struct Descriptor {
+    pub(crate) id: AttributeId,
     pub(crate) uuid: Uuid,
     pub(crate) properties: Properties,
-    pub(crate) value: Option<Vec<u8>>,
+    pub(crate) value: StoredValue,
+    pub(crate) authorize: bool,
+    pub(crate) response_deadline: Option<ResponseDeadline>,
+    pub(crate) long_read_cache: bool,
+    pub(crate) long_write_assembly: bool,
+    pub(crate) handle: Option<u16>,
 }
 
 impl Descriptor {
     pub fn new(uuid: Uuid, properties: Properties, value: Option<Vec<u8>>) -> Self {
         Descriptor {
+            id: AttributeId::next(),
             uuid,
             properties,
-            value,
+            value: StoredValue::new(value),
+            authorize: false,
+            response_deadline: None,
+            long_read_cache: false,
+            long_write_assembly: false,
+            handle: None,
         }
     }
+
+    /// Tells events for this attribute apart from those of others sharing the handler
+    pub fn id(&self) -> AttributeId {
+        self.id
+    }
+
+    /// The value reads are answered with when there's no read handler
+    pub fn value(&self) -> Option<Vec<u8>> {
+        self.value.get()
+    }
+
+    /// Replaces the value shared with every clone of the descriptor
+    pub fn set_value(&self, value: Vec<u8>) {
+        self.value.set(value);
+    }
+
+    /// Asks BlueZ to place the descriptor at `handle`, so it keeps it across restarts. Registering
+    /// the application fails when it can't.
+    pub fn with_handle(mut self, handle: u16) -> Self {
+        self.handle = Some(handle);
+        self
+    }
+
+    /// Asks the handler for an `Authorization` with the requesting central before every read
+    /// and write, including those of a stored value. Without a read handler, the write handler
+    /// is asked.
+    pub fn with_authorize(mut self, authorize: bool) -> Self {
+        self.authorize = authorize;
+        self
+    }
+
+    /// Overrides the deadline set on the peripheral for requests to this attribute
+    pub fn with_response_deadline(mut self, response_deadline: ResponseDeadline) -> Self {
+        self.response_deadline = Some(response_deadline);
+        self
+    }
+
+    /// Only asks the read handler for the whole value at offset 0, and serves the parts of a
+    /// long read from it, so they're consistent and the handler doesn't need to slice
+    pub fn with_long_read_cache(mut self, long_read_cache: bool) -> Self {
+        self.long_read_cache = long_read_cache;
+        self
+    }
+
+    /// Collects the fragments of long writes, so the handler receives a single `WriteRequest`
+    /// holding the whole value at offset 0. Works like `Characteristic::with_long_write_assembly`.
+    pub fn with_long_write_assembly(mut self, long_write_assembly: bool) -> Self {
+        self.long_write_assembly = long_write_assembly;
+        self
+    }
 }
 
-impl_uuid_hash_eq!(Descriptor);
+impl_id_hash_eq!(Descriptor);
 
 properties!(WriteWithResponse, EventSender);
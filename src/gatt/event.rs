@@ -1,19 +1,81 @@
-use futures::channel::{mpsc, oneshot};
+use futures::{
+    channel::{mpsc, oneshot},
+    future::Shared,
+    prelude::*,
+    stream::BoxStream,
+};
+use std::{error, fmt, time::Duration};
+
+pub use super::notification_queue::{Delivery, NotificationError, QueueReceiver};
+use super::{
+    notification_queue::{self, NotificationQueue, QueueSender},
+    Attribute,
+};
+use crate::Central;
 
 pub type EventSender = mpsc::Sender<Event>;
 pub type ResponseSender = oneshot::Sender<Response>;
+pub type AuthorizationSender = oneshot::Sender<Authorization>;
+pub type PayloadStream = BoxStream<'static, Vec<u8>>;
+pub type ConfirmationSender = oneshot::Sender<()>;
 
 #[derive(Debug)]
 pub enum Event {
+    AuthorizeRequest(AuthorizeRequest),
     ReadRequest(ReadRequest),
     WriteRequest(WriteRequest),
+    WriteAcquired(WriteAcquired),
     NotifySubscribe(NotifySubscribe),
-    NotifyUnsubscribe,
+    NotifyUnsubscribe(Attribute),
+    IndicateSubscribe(IndicateSubscribe),
+    IndicateUnsubscribe(Attribute),
+}
+
+impl Event {
+    /// The attribute the event was sent for, so one handler can serve several of them
+    pub fn attribute(&self) -> &Attribute {
+        match self {
+            Event::AuthorizeRequest(request) => &request.attribute,
+            Event::ReadRequest(request) => &request.attribute,
+            Event::WriteRequest(request) => &request.attribute,
+            Event::WriteAcquired(acquired) => &acquired.attribute,
+            Event::NotifySubscribe(subscribe) => &subscribe.attribute,
+            Event::IndicateSubscribe(subscribe) => &subscribe.attribute,
+            Event::NotifyUnsubscribe(attribute) | Event::IndicateUnsubscribe(attribute) => {
+                attribute
+            }
+        }
+    }
+}
+
+/// Sent before a read or write of an attribute which requires authorization, the request only
+/// reaches the handler when it's granted
+#[derive(Debug)]
+pub struct AuthorizeRequest {
+    pub attribute: Attribute,
+    pub central: Option<Central>,
+    pub operation: Operation,
+    pub offset: u16,
+    pub response: AuthorizationSender,
+    pub mtu: u16,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    Read,
+    Write,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Authorization {
+    Granted,
+    Denied,
 }
 
 #[derive(Debug)]
 #[non_exhaustive]
 pub struct ReadRequest {
+    pub attribute: Attribute,
     pub offset: u16,
     pub response: ResponseSender,
     pub mtu: u16,
@@ -21,21 +83,224 @@ pub struct ReadRequest {
 
 #[derive(Debug)]
 pub struct WriteRequest {
+    pub attribute: Attribute,
     pub data: Vec<u8>,
     pub offset: u16,
+    /// Set for write commands, the central doesn't wait for a response, so there's no need to
+    /// send one
     pub without_response: bool,
     pub response: ResponseSender,
+    pub mtu: u16,
+}
+
+/// Sent when a central starts writing without response through a socket, those writes arrive
+/// on `payloads` instead of as `WriteRequest`s until the stream ends
+pub struct WriteAcquired {
+    pub attribute: Attribute,
+    pub central: Option<Central>,
+    pub mtu: u16,
+    pub payloads: PayloadStream,
+}
+
+impl fmt::Debug for WriteAcquired {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("WriteAcquired")
+            .field("attribute", &self.attribute)
+            .field("central", &self.central)
+            .field("mtu", &self.mtu)
+            .finish()
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct NotifySubscribe {
-    pub notification: mpsc::Sender<Vec<u8>>,
+    pub attribute: Attribute,
+    /// Unknown on BlueZ, unless the subscription was acquired through a socket
+    pub central: Option<Central>,
+    pub notification: Subscription<Vec<u8>>,
+    pub mtu: u16,
 }
 
+/// Sends values to the centrals subscribed to a characteristic. It's closed once they
+/// unsubscribe, which BlueZ also does for centrals that disconnect.
+#[derive(Debug)]
+pub struct Subscription<T> {
+    sender: QueueSender<T>,
+    closed: Shared<oneshot::Receiver<()>>,
+}
+
+impl<T> Clone for Subscription<T> {
+    fn clone(&self) -> Self {
+        Subscription {
+            sender: self.sender.clone(),
+            closed: self.closed.clone(),
+        }
+    }
+}
+
+impl<T> Subscription<T> {
+    /// Values wait for the receiver in a queue, the subscription closes when the receiver or
+    /// the sender of `closed` is dropped
+    pub fn new(
+        queue: NotificationQueue,
+        closed: oneshot::Receiver<()>,
+    ) -> (Self, QueueReceiver<T>) {
+        let (sender, receiver) = notification_queue::channel(queue);
+        let subscription = Subscription {
+            sender,
+            closed: closed.shared(),
+        };
+        (subscription, receiver)
+    }
+
+    /// Queues a value, following the policy of the queue when it's full
+    pub async fn send(&self, value: T) -> Result<(), NotificationError> {
+        if self.is_closed() {
+            return Err(NotificationError::Closed);
+        }
+        self.sender.push(value, None).await
+    }
+
+    /// Queues a value and waits until it's handed to the platform, or fails to be
+    pub async fn deliver(&self, value: T) -> Result<(), NotificationError> {
+        if self.is_closed() {
+            return Err(NotificationError::Closed);
+        }
+        let (report, reported) = oneshot::channel();
+        self.sender.push(value, Some(report)).await?;
+        reported.await.unwrap_or(Err(NotificationError::Closed))
+    }
+
+    /// Queues a value without waiting for room
+    pub fn try_send(&self, value: T) -> Result<(), NotificationError> {
+        if self.is_closed() {
+            return Err(NotificationError::Closed);
+        }
+        self.sender.push_now(value)
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.sender.is_closed() || self.closed.peek().is_some()
+    }
+
+    /// Resolves once the centrals unsubscribed
+    pub fn closed(&self) -> impl Future<Output = ()> {
+        self.closed.clone().map(|_| ())
+    }
+}
+
+/// Sent to the `indicate` handler of a characteristic when a central subscribes. BlueZ doesn't
+/// tell which of the two a central enabled, so when a characteristic supports notifications as
+/// well, only its `notify` handler gets a `NotifySubscribe` instead.
+#[derive(Debug, Clone)]
+pub struct IndicateSubscribe {
+    pub attribute: Attribute,
+    /// Unknown on BlueZ, unless the subscription was acquired through a socket
+    pub central: Option<Central>,
+    pub indication: Indicator,
+    pub mtu: u16,
+}
+
+#[derive(Debug)]
+pub struct Indication {
+    pub value: Vec<u8>,
+    pub confirmation: ConfirmationSender,
+}
+
+#[derive(Debug, Clone)]
+pub struct Indicator {
+    subscription: Subscription<Indication>,
+    timeout: Duration,
+}
+
+impl Indicator {
+    pub fn new(subscription: Subscription<Indication>, timeout: Duration) -> Self {
+        Indicator {
+            subscription,
+            timeout,
+        }
+    }
+
+    /// Sends an indication, resolving once the central confirms it. BlueZ doesn't tell which
+    /// central a confirmation comes from, so with several centrals subscribed, it resolves with
+    /// the first one.
+    pub async fn indicate(&self, value: Vec<u8>) -> Result<(), IndicationError> {
+        let (confirmation, confirmed) = oneshot::channel();
+        self.subscription
+            .deliver(Indication {
+                value,
+                confirmation,
+            })
+            .await
+            .map_err(|err| match err {
+                NotificationError::Closed => IndicationError::Closed,
+                err => IndicationError::Undelivered(err),
+            })?;
+        match tokio::time::timeout(self.timeout, confirmed).await {
+            Ok(Ok(())) => Ok(()),
+            Ok(Err(_)) => Err(IndicationError::Closed),
+            Err(_) => Err(IndicationError::Timeout),
+        }
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.subscription.is_closed()
+    }
+
+    /// Resolves once the centrals unsubscribed
+    pub fn closed(&self) -> impl Future<Output = ()> {
+        self.subscription.closed()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IndicationError {
+    /// The subscription ended before the indication was confirmed
+    Closed,
+    /// No confirmation arrived in time
+    Timeout,
+    Undelivered(NotificationError),
+}
+
+impl fmt::Display for IndicationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IndicationError::Closed => write!(f, "Subscription closed"),
+            IndicationError::Timeout => write!(f, "Indication wasn't confirmed in time"),
+            IndicationError::Undelivered(err) => write!(f, "Indication not sent: {}", err),
+        }
+    }
+}
+
+impl error::Error for IndicationError {}
+
+/// The answer to a request, every variant besides `Success` reaches the central as the ATT
+/// error code noted on it. BlueZ only passes on application error codes though, it sends the
+/// codes of profile errors, from 0xE0 up, as 0x80.
 #[derive(Debug, Clone)]
 pub enum Response {
     Success(Vec<u8>),
+    /// 0x07
     InvalidOffset,
+    /// 0x0D
     InvalidAttributeLength,
+    /// 0x0E
     UnlikelyError,
+    /// 0x02 for reads, 0x03 for writes
+    NotPermitted,
+    /// 0x08
+    NotAuthorized,
+    /// 0x06
+    NotSupported,
+    /// An error defined by the application, from 0x80 up to 0x9F. Other codes are sent as
+    /// `UnlikelyError`.
+    ApplicationError(u8),
+    /// 0xFC, 0x80 on BlueZ
+    WriteRequestRejected,
+    /// 0xFD, 0x80 on BlueZ
+    CccdImproperlyConfigured,
+    /// 0xFE
+    ProcedureAlreadyInProgress,
+    /// 0xFF, 0x80 on BlueZ
+    OutOfRange,
 }
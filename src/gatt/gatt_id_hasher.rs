@@ -0,0 +1,18 @@
+// Attributes are told apart by their instance, as a parent may hold several with the same UUID
+macro_rules! impl_id_hash_eq {
+    ($struct_with_id_member:ident) => {
+        impl Hash for $struct_with_id_member {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                self.id.hash(state);
+            }
+        }
+
+        impl PartialEq for $struct_with_id_member {
+            fn eq(&self, other: &$struct_with_id_member) -> bool {
+                self.id == other.id
+            }
+        }
+
+        impl Eq for $struct_with_id_member {}
+    };
+}
@@ -4,6 +4,8 @@ macro_rules! _write_type {
         pub enum Write {
             WithResponse($secure),
             WithoutResponse($event_sender),
+            /// Accepts both, `WriteRequest::without_response` tells them apart
+            WithAndWithoutResponse($secure),
         }
 
         impl Write {
@@ -11,6 +13,7 @@ macro_rules! _write_type {
                 match self {
                     Write::WithResponse(event_sender) => event_sender.sender(),
                     Write::WithoutResponse(event_sender) => event_sender,
+                    Write::WithAndWithoutResponse(event_sender) => event_sender.sender(),
                 }
             }
         }
@@ -60,6 +63,14 @@ macro_rules! _properties {
             pub fn is_read_only(self: &Self) -> bool {
                 self.read.is_some() && self.write.is_none()
             }
+
+            /// Authorization requests go to the read handler, or the write handler without one
+            pub(crate) fn authorization_sender(&self) -> Option<$event_sender> {
+                self.read
+                    .clone()
+                    .map(Read::sender)
+                    .or_else(|| self.write.clone().map(Write::sender))
+            }
         }
 
         #[derive(Debug, Clone)]
@@ -81,15 +92,43 @@ macro_rules! _properties {
 
         #[derive(Debug, Clone)]
         pub enum Secure {
+            /// Requires `SecurityLevel::SecureConnections`
             Secure($event_sender),
             Insecure($event_sender),
+            Encrypted($event_sender),
+            Authenticated($event_sender),
         }
 
         impl Secure {
+            pub fn new(
+                security_level: $crate::gatt::SecurityLevel,
+                event_sender: $event_sender,
+            ) -> Self {
+                match security_level {
+                    $crate::gatt::SecurityLevel::None => Secure::Insecure(event_sender),
+                    $crate::gatt::SecurityLevel::Encrypted => Secure::Encrypted(event_sender),
+                    $crate::gatt::SecurityLevel::Authenticated => {
+                        Secure::Authenticated(event_sender)
+                    }
+                    $crate::gatt::SecurityLevel::SecureConnections => Secure::Secure(event_sender),
+                }
+            }
+
+            pub fn security_level(self: &Self) -> $crate::gatt::SecurityLevel {
+                match self {
+                    Secure::Secure(_) => $crate::gatt::SecurityLevel::SecureConnections,
+                    Secure::Insecure(_) => $crate::gatt::SecurityLevel::None,
+                    Secure::Encrypted(_) => $crate::gatt::SecurityLevel::Encrypted,
+                    Secure::Authenticated(_) => $crate::gatt::SecurityLevel::Authenticated,
+                }
+            }
+
             pub fn sender(self: Self) -> $event_sender {
                 match self {
                     Secure::Secure(event_sender) => event_sender,
                     Secure::Insecure(event_sender) => event_sender,
+                    Secure::Encrypted(event_sender) => event_sender,
+                    Secure::Authenticated(event_sender) => event_sender,
                 }
             }
         }
@@ -4,10 +4,27 @@
 mod gatt_properties;
 
 #[macro_use]
-mod gatt_uuid_hasher;
+mod gatt_id_hasher;
 
+mod attribute;
 pub mod characteristic;
 pub mod descriptor;
+pub(crate) mod notification_queue;
+mod response_deadline;
+mod security_level;
 pub mod service;
+mod stored_value;
+mod subscribers;
+pub(crate) mod validation;
+
+pub(crate) use self::stored_value::StoredValue;
+pub use self::{
+    attribute::{Attribute, AttributeId},
+    notification_queue::{NotificationQueue, QueuePolicy},
+    response_deadline::ResponseDeadline,
+    security_level::SecurityLevel,
+    subscribers::Subscribers,
+    validation::{validate, Problem},
+};
 
 pub mod event;
@@ -0,0 +1,238 @@
+use futures::channel::oneshot;
+use std::{
+    collections::VecDeque,
+    error, fmt,
+    sync::{Arc, Mutex},
+};
+use tokio::sync::Notify;
+
+/// What happens to a value sent while the queue of a subscription is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueuePolicy {
+    /// Wait until there's room again
+    Block,
+    /// Make room by dropping the value which waited the longest
+    DropOldest,
+    /// Drop the value being sent
+    DropNewest,
+    /// Replace the last queued value, so the central gets the latest one
+    Coalesce,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NotificationQueue {
+    pub depth: usize,
+    pub policy: QueuePolicy,
+}
+
+impl NotificationQueue {
+    pub fn new(depth: usize, policy: QueuePolicy) -> Self {
+        NotificationQueue {
+            depth: depth.max(1),
+            policy,
+        }
+    }
+}
+
+impl Default for NotificationQueue {
+    fn default() -> Self {
+        NotificationQueue::new(1, QueuePolicy::Block)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NotificationError {
+    /// The centrals unsubscribed
+    Closed,
+    /// The queue was full and its policy doesn't block
+    Full,
+    /// Dropped by the policy of the queue
+    Dropped,
+    /// Handing the value to the platform failed
+    Failed(String),
+}
+
+impl fmt::Display for NotificationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NotificationError::Closed => write!(f, "Subscription closed"),
+            NotificationError::Full => write!(f, "Queue full"),
+            NotificationError::Dropped => write!(f, "Dropped by the queue policy"),
+            NotificationError::Failed(reason) => write!(f, "Delivery failed: {}", reason),
+        }
+    }
+}
+
+impl error::Error for NotificationError {}
+
+pub type ReportSender = oneshot::Sender<Result<(), NotificationError>>;
+
+/// A queued value, along with where to report the outcome of its delivery
+#[derive(Debug)]
+pub struct Delivery<T> {
+    pub value: T,
+    report: Option<ReportSender>,
+}
+
+/// Hands the outcome of a delivery to whoever waits for it, failures nobody waits for are logged
+pub fn report(report: Option<ReportSender>, result: Result<(), NotificationError>) {
+    match report {
+        Some(report) => {
+            report.send(result).ok();
+        }
+        None => {
+            if let Err(err) = result {
+                log::warn!("Notification not delivered: {}", err);
+            }
+        }
+    }
+}
+
+impl<T> Delivery<T> {
+    pub fn into_parts(self) -> (T, Option<ReportSender>) {
+        (self.value, self.report)
+    }
+
+    fn fail(self, err: NotificationError) {
+        report(self.report, Err(err));
+    }
+}
+
+#[derive(Debug)]
+struct Inner<T> {
+    deliveries: VecDeque<Delivery<T>>,
+    closed: bool,
+}
+
+#[derive(Debug)]
+struct Shared<T> {
+    inner: Mutex<Inner<T>>,
+    queue: NotificationQueue,
+    readable: Notify,
+    writable: Notify,
+}
+
+pub fn channel<T>(queue: NotificationQueue) -> (QueueSender<T>, QueueReceiver<T>) {
+    let shared = Arc::new(Shared {
+        inner: Mutex::new(Inner {
+            deliveries: VecDeque::new(),
+            closed: false,
+        }),
+        queue,
+        readable: Notify::new(),
+        writable: Notify::new(),
+    });
+    (QueueSender(Arc::clone(&shared)), QueueReceiver(shared))
+}
+
+#[derive(Debug)]
+pub struct QueueSender<T>(Arc<Shared<T>>);
+
+impl<T> Clone for QueueSender<T> {
+    fn clone(&self) -> Self {
+        QueueSender(Arc::clone(&self.0))
+    }
+}
+
+impl<T> QueueSender<T> {
+    /// Queues a value following the policy, handing it back when it has to wait for room
+    fn try_push(
+        &self,
+        delivery: Delivery<T>,
+    ) -> Result<(), Result<Delivery<T>, NotificationError>> {
+        let mut inner = self.0.inner.lock().unwrap();
+        if inner.closed {
+            return Err(Err(NotificationError::Closed));
+        }
+
+        let evicted = if inner.deliveries.len() < self.0.queue.depth {
+            None
+        } else {
+            match self.0.queue.policy {
+                QueuePolicy::Block => return Err(Ok(delivery)),
+                QueuePolicy::DropNewest => return Err(Err(NotificationError::Dropped)),
+                QueuePolicy::DropOldest => inner.deliveries.pop_front(),
+                QueuePolicy::Coalesce => inner.deliveries.pop_back(),
+            }
+        };
+        inner.deliveries.push_back(delivery);
+        drop(inner);
+
+        self.0.readable.notify_one();
+        if let Some(evicted) = evicted {
+            evicted.fail(NotificationError::Dropped);
+        }
+        Ok(())
+    }
+
+    /// Queues a value, the outcome of its delivery is sent to `report` once it's dequeued
+    pub async fn push(
+        &self,
+        value: T,
+        report: Option<ReportSender>,
+    ) -> Result<(), NotificationError> {
+        let mut delivery = Delivery { value, report };
+        loop {
+            let writable = self.0.writable.notified();
+            match self.try_push(delivery) {
+                Ok(()) => return Ok(()),
+                Err(Err(err)) => return Err(err),
+                Err(Ok(waiting)) => delivery = waiting,
+            }
+            writable.await;
+        }
+    }
+
+    pub fn push_now(&self, value: T) -> Result<(), NotificationError> {
+        match self.try_push(Delivery {
+            value,
+            report: None,
+        }) {
+            Ok(()) => Ok(()),
+            Err(Err(err)) => Err(err),
+            Err(Ok(_)) => Err(NotificationError::Full),
+        }
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.0.inner.lock().unwrap().closed
+    }
+}
+
+#[derive(Debug)]
+pub struct QueueReceiver<T>(Arc<Shared<T>>);
+
+impl<T> QueueReceiver<T> {
+    /// The next queued value, `None` once closed
+    pub async fn recv(&self) -> Option<Delivery<T>> {
+        loop {
+            let readable = self.0.readable.notified();
+            {
+                let mut inner = self.0.inner.lock().unwrap();
+                if inner.closed {
+                    return None;
+                }
+                if let Some(delivery) = inner.deliveries.pop_front() {
+                    drop(inner);
+                    self.0.writable.notify_one();
+                    return Some(delivery);
+                }
+            }
+            readable.await;
+        }
+    }
+}
+
+impl<T> Drop for QueueReceiver<T> {
+    fn drop(&mut self) {
+        let deliveries = {
+            let mut inner = self.0.inner.lock().unwrap();
+            inner.closed = true;
+            inner.deliveries.drain(..).collect::<Vec<_>>()
+        };
+        for delivery in deliveries {
+            delivery.fail(NotificationError::Closed);
+        }
+        self.0.writable.notify_waiters();
+    }
+}
@@ -0,0 +1,18 @@
+use std::time::Duration;
+
+use super::event::Response;
+
+/// How long a handler has to answer a read or write request, and the response the central
+/// gets when it doesn't, or drops the `ResponseSender`. Authorization requests left unanswered
+/// until the deadline are denied.
+#[derive(Debug, Clone)]
+pub struct ResponseDeadline {
+    pub timeout: Duration,
+    pub response: Response,
+}
+
+impl ResponseDeadline {
+    pub fn new(timeout: Duration, response: Response) -> Self {
+        ResponseDeadline { timeout, response }
+    }
+}
@@ -0,0 +1,12 @@
+/// The security a central's connection needs before it may access an attribute
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SecurityLevel {
+    /// Open access (LE security mode 1, level 1)
+    None,
+    /// An encrypted link, which may come from unauthenticated Just Works pairing (level 2)
+    Encrypted,
+    /// An encrypted link from pairing with MITM protection (level 3)
+    Authenticated,
+    /// An encrypted link from authenticated LE Secure Connections pairing (level 4)
+    SecureConnections,
+}
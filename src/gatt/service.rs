@@ -1,20 +1,51 @@
 use super::characteristic::Characteristic;
-use std::collections::HashSet;
 use uuid::Uuid;
 
+/// Refers to a service added to a peripheral, to remove it again
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ServiceHandle(u64);
+
+impl ServiceHandle {
+    pub(crate) fn new(index: u64) -> Self {
+        ServiceHandle(index)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Service {
     pub(crate) uuid: Uuid,
     pub(crate) primary: bool,
-    pub(crate) characteristics: HashSet<Characteristic>,
+    pub(crate) characteristics: Vec<Characteristic>,
+    pub(crate) includes: Vec<ServiceHandle>,
+    pub(crate) handle: Option<u16>,
 }
 
 impl Service {
-    pub fn new(uuid: Uuid, primary: bool, characteristics: HashSet<Characteristic>) -> Self {
+    /// Characteristics are registered in the order they're given, which decides their handles
+    pub fn new<I: IntoIterator<Item = Characteristic>>(
+        uuid: Uuid,
+        primary: bool,
+        characteristics: I,
+    ) -> Self {
         Service {
             uuid,
             primary,
-            characteristics,
+            characteristics: characteristics.into_iter().collect(),
+            includes: vec![],
+            handle: None,
         }
     }
+
+    /// Asks BlueZ to place the service at `handle`, so it keeps it across restarts. Registering
+    /// the application fails when it can't.
+    pub fn with_handle(mut self, handle: u16) -> Self {
+        self.handle = Some(handle);
+        self
+    }
+
+    /// Includes services added to the same peripheral before this one
+    pub fn with_includes<I: IntoIterator<Item = ServiceHandle>>(mut self, includes: I) -> Self {
+        self.includes = includes.into_iter().collect();
+        self
+    }
 }
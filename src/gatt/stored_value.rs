@@ -0,0 +1,53 @@
+use std::sync::{Arc, RwLock};
+use tokio::sync::watch;
+
+#[derive(Debug, Clone)]
+enum Source {
+    Fixed(Option<Vec<u8>>),
+    Watch(watch::Receiver<Vec<u8>>),
+}
+
+/// The value of a characteristic or descriptor kept by bluster, shared by all of its clones
+#[derive(Debug, Clone)]
+pub struct StoredValue {
+    source: Arc<RwLock<Source>>,
+}
+
+impl StoredValue {
+    pub fn new(value: Option<Vec<u8>>) -> Self {
+        StoredValue {
+            source: Arc::new(RwLock::new(Source::Fixed(value))),
+        }
+    }
+
+    /// Follows the latest value sent on the channel of `receiver`
+    pub fn watch(receiver: watch::Receiver<Vec<u8>>) -> Self {
+        StoredValue {
+            source: Arc::new(RwLock::new(Source::Watch(receiver))),
+        }
+    }
+
+    pub fn get(&self) -> Option<Vec<u8>> {
+        match &*self.source.read().unwrap() {
+            Source::Fixed(value) => value.clone(),
+            Source::Watch(receiver) => Some(receiver.borrow().clone()),
+        }
+    }
+
+    /// Replaces the value, which stops following a watch channel
+    pub fn set(&self, value: Vec<u8>) {
+        *self.source.write().unwrap() = Source::Fixed(Some(value));
+    }
+
+    /// A receiver which only sees changes made from now on, if the value follows a channel
+    pub fn changes(&self) -> Option<watch::Receiver<Vec<u8>>> {
+        match &*self.source.read().unwrap() {
+            Source::Fixed(_) => None,
+            Source::Watch(receiver) => {
+                let mut receiver = receiver.clone();
+                receiver.borrow_and_update();
+                Some(receiver)
+            }
+        }
+    }
+}
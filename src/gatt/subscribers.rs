@@ -0,0 +1,79 @@
+use std::sync::{Arc, Mutex};
+
+use super::event::{NotificationError, Subscription};
+use crate::Central;
+
+#[derive(Debug)]
+struct Subscriber {
+    central: Option<Central>,
+    subscription: Subscription<Vec<u8>>,
+}
+
+/// The subscriptions to the notifications of a characteristic, shared by all of its clones.
+///
+/// BlueZ only asks a characteristic to start notifying for the first central and to stop after
+/// the last one, so there it holds a single subscription standing in for all of them, which
+/// only knows its central when acquired through a socket.
+#[derive(Debug, Clone, Default)]
+pub struct Subscribers {
+    subscribers: Arc<Mutex<Vec<Subscriber>>>,
+}
+
+impl Subscribers {
+    /// Done by the peripheral whenever a central subscribes
+    pub fn insert(&self, central: Option<Central>, subscription: Subscription<Vec<u8>>) {
+        self.subscribers.lock().unwrap().push(Subscriber {
+            central,
+            subscription,
+        });
+    }
+
+    fn open(&self) -> Vec<(Option<Central>, Subscription<Vec<u8>>)> {
+        let mut subscribers = self.subscribers.lock().unwrap();
+        subscribers.retain(|subscriber| !subscriber.subscription.is_closed());
+        subscribers
+            .iter()
+            .map(|subscriber| (subscriber.central.clone(), subscriber.subscription.clone()))
+            .collect()
+    }
+
+    /// How many subscriptions are open. On BlueZ, where one subscription stands in for every
+    /// central, that's whether any central is subscribed rather than how many are.
+    pub fn subscription_count(&self) -> usize {
+        self.open().len()
+    }
+
+    /// Sends `value` to every subscriber, returning how many it reached
+    pub async fn notify(&self, value: Vec<u8>) -> usize {
+        let mut notified = 0;
+        for (_, subscription) in self.open() {
+            if subscription.send(value.clone()).await.is_ok() {
+                notified += 1;
+            }
+        }
+        notified
+    }
+
+    /// Sends `value` to the subscriptions known to be made by `central` only. On BlueZ, that's
+    /// the one acquired through a socket, and BlueZ sends what goes through it to every
+    /// subscribed central, not just the one which acquired it.
+    pub async fn notify_subscriptions_of(
+        &self,
+        central: &Central,
+        value: Vec<u8>,
+    ) -> Result<(), NotificationError> {
+        let mut subscriptions = self
+            .open()
+            .into_iter()
+            .filter(|(subscriber, _)| subscriber.as_ref() == Some(central))
+            .map(|(_, subscription)| subscription)
+            .peekable();
+        if subscriptions.peek().is_none() {
+            return Err(NotificationError::Closed);
+        }
+        for subscription in subscriptions {
+            subscription.send(value.clone()).await?;
+        }
+        Ok(())
+    }
+}
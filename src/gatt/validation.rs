@@ -0,0 +1,195 @@
+use std::fmt;
+use uuid::Uuid;
+
+use super::{
+    attribute::AttributeId,
+    characteristic::{Characteristic, Write},
+    descriptor::Descriptor,
+    service::Service,
+};
+use crate::{Error, SdpShortUuid};
+
+// Client Characteristic Configuration
+const CCCD_UUID: u16 = 0x2902;
+// Characteristic User Description
+const USER_DESCRIPTION_UUID: u16 = 0x2901;
+
+/// A mistake in the definition of a service, found before it reaches the platform. Attributes
+/// are told apart by their id, since several of them may share a UUID.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+    /// The platform manages the Client Characteristic Configuration descriptor (0x2902) itself
+    CccdDescriptor {
+        characteristic: AttributeId,
+        uuid: Uuid,
+    },
+    /// Centrals may subscribe, but nothing hands out their subscriptions: the receiver of the
+    /// `notify` or `indicate` sender is gone, or notifications are acquired through a socket
+    /// without either sender or a watch channel
+    NotifyWithoutSender {
+        characteristic: AttributeId,
+        uuid: Uuid,
+    },
+    /// Services need at least one characteristic
+    EmptyService { service: Uuid },
+    /// The characteristic or descriptor is configured in a way that can't work
+    InvalidFlags {
+        attribute: AttributeId,
+        uuid: Uuid,
+        reason: &'static str,
+    },
+    /// Another service added to the peripheral has the same UUID
+    DuplicateService { service: Uuid },
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Problem::CccdDescriptor {
+                characteristic,
+                uuid,
+            } => write!(
+                f,
+                "Characteristic {} ({}) has a CCCD, which is managed by the platform",
+                uuid, characteristic
+            ),
+            Problem::NotifyWithoutSender {
+                characteristic,
+                uuid,
+            } => write!(
+                f,
+                "Characteristic {} ({}) has no sender for its subscriptions",
+                uuid, characteristic
+            ),
+            Problem::EmptyService { service } => {
+                write!(f, "Service {} has no characteristics", service)
+            }
+            Problem::InvalidFlags {
+                attribute,
+                uuid,
+                reason,
+            } => write!(f, "{} ({}): {}", uuid, attribute, reason),
+            Problem::DuplicateService { service } => {
+                write!(f, "Service {} was already added", service)
+            }
+        }
+    }
+}
+
+/// Checks a service for every known problem at once
+pub fn validate(service: &Service) -> Result<(), Error> {
+    match problems(service) {
+        problems if problems.is_empty() => Ok(()),
+        problems => Err(Error::invalid(problems)),
+    }
+}
+
+pub(crate) fn problems(service: &Service) -> Vec<Problem> {
+    let mut problems = vec![];
+    if service.characteristics.is_empty() {
+        problems.push(Problem::EmptyService {
+            service: service.uuid,
+        });
+    }
+    for characteristic in service.characteristics.iter() {
+        characteristic_problems(characteristic, &mut problems);
+        for descriptor in characteristic.descriptors.iter() {
+            if descriptor.uuid == Uuid::from_sdp_short_uuid(CCCD_UUID) {
+                problems.push(Problem::CccdDescriptor {
+                    characteristic: characteristic.id,
+                    uuid: characteristic.uuid,
+                });
+            }
+            descriptor_problems(descriptor, &mut problems);
+        }
+    }
+    problems
+}
+
+fn characteristic_problems(characteristic: &Characteristic, problems: &mut Vec<Problem>) {
+    let properties = &characteristic.properties;
+    let readable = properties.read.is_some() || characteristic.value.get().is_some();
+    let writable = properties.write.is_some() || characteristic.value_writes.is_some();
+    let notifiable = properties.notify.is_some()
+        || properties.indicate.is_some()
+        || characteristic.value_changes.is_some();
+    let mut invalid = |reason| {
+        problems.push(Problem::InvalidFlags {
+            attribute: characteristic.id,
+            uuid: characteristic.uuid,
+            reason,
+        })
+    };
+
+    if !readable && !writable && !notifiable {
+        invalid("Can't be read, written or subscribed to");
+    }
+    if characteristic.acquire_write
+        && !matches!(
+            properties.write,
+            Some(Write::WithoutResponse(_)) | Some(Write::WithAndWithoutResponse(_))
+        )
+    {
+        invalid("Acquiring writes requires writes without response");
+    }
+    if characteristic.reliable_write
+        && !(characteristic.value_writes.is_some()
+            || matches!(
+                properties.write,
+                Some(Write::WithResponse(_)) | Some(Write::WithAndWithoutResponse(_))
+            ))
+    {
+        invalid("Reliable writes require writes with response");
+    }
+    if characteristic.writable_auxiliaries
+        && !characteristic
+            .descriptors
+            .iter()
+            .any(|descriptor| descriptor.uuid == Uuid::from_sdp_short_uuid(USER_DESCRIPTION_UUID))
+    {
+        invalid("Writable auxiliaries require a User Description descriptor");
+    }
+    if characteristic.long_write_assembly && properties.write.is_none() {
+        invalid("Long write assembly requires a write handler");
+    }
+    if characteristic.long_read_cache && properties.read.is_none() {
+        invalid("Long read caching requires a read handler");
+    }
+    if characteristic.authorize && properties.read.is_none() && properties.write.is_none() {
+        invalid("Authorization requires a read or write handler");
+    }
+
+    let closed = [&properties.notify, &properties.indicate]
+        .iter()
+        .any(|sender| matches!(sender, Some(sender) if sender.is_closed()));
+    if closed || (characteristic.acquire_notify && !notifiable) {
+        problems.push(Problem::NotifyWithoutSender {
+            characteristic: characteristic.id,
+            uuid: characteristic.uuid,
+        });
+    }
+}
+
+fn descriptor_problems(descriptor: &Descriptor, problems: &mut Vec<Problem>) {
+    let properties = &descriptor.properties;
+    let mut invalid = |reason| {
+        problems.push(Problem::InvalidFlags {
+            attribute: descriptor.id,
+            uuid: descriptor.uuid,
+            reason,
+        })
+    };
+
+    if properties.read.is_none() && properties.write.is_none() && descriptor.value.get().is_none() {
+        invalid("Can't be read or written");
+    }
+    if descriptor.long_write_assembly && properties.write.is_none() {
+        invalid("Long write assembly requires a write handler");
+    }
+    if descriptor.long_read_cache && properties.read.is_none() {
+        invalid("Long read caching requires a read handler");
+    }
+    if descriptor.authorize && properties.read.is_none() && properties.write.is_none() {
+        invalid("Authorization requires a read or write handler");
+    }
+}
@@ -2,9 +2,23 @@
 // warnings caused by `ATOMIC_USIZE_INIT` being deprecated
 #![allow(deprecated)]
 
+mod agent;
+mod bond;
+mod central;
 mod error;
 pub mod gatt;
 mod peripheral;
 mod uuid;
 
-pub use self::{error::*, peripheral::Peripheral, uuid::*};
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub use self::peripheral::{AcquiredSocket, Agent};
+pub use self::{
+    agent::*,
+    bond::Bond,
+    central::Central,
+    error::*,
+    peripheral::{
+        MtuChanged, Peripheral, PeripheralEvent, PeripheralEventSender, ResponseTimedOut,
+    },
+    uuid::*,
+};
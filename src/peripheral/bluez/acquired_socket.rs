@@ -0,0 +1,130 @@
+use futures::{prelude::*, stream};
+use std::{
+    io,
+    os::unix::io::{AsRawFd, FromRawFd, OwnedFd},
+};
+use tokio::io::unix::AsyncFd;
+
+use crate::gatt::event::PayloadStream;
+
+/// One end of the `SOCK_SEQPACKET` socket pair behind `AcquireWrite` and `AcquireNotify`, every
+/// packet holds a whole attribute value
+#[derive(Debug)]
+pub struct AcquiredSocket {
+    fd: AsyncFd<OwnedFd>,
+    mtu: u16,
+}
+
+impl AcquiredSocket {
+    /// Creates a connected pair, returning the end to keep and the one to hand over
+    pub fn pair(mtu: u16) -> io::Result<(Self, OwnedFd)> {
+        let mut fds = [0; 2];
+        let result = unsafe {
+            libc::socketpair(
+                libc::AF_UNIX,
+                libc::SOCK_SEQPACKET | libc::SOCK_NONBLOCK | libc::SOCK_CLOEXEC,
+                0,
+                fds.as_mut_ptr(),
+            )
+        };
+        if result < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let (local, remote) =
+            unsafe { (OwnedFd::from_raw_fd(fds[0]), OwnedFd::from_raw_fd(fds[1])) };
+        Ok((AcquiredSocket::new(local, mtu)?, remote))
+    }
+
+    /// Wraps a non-blocking socket, packets are received into buffers of `mtu` bytes
+    pub fn new(fd: OwnedFd, mtu: u16) -> io::Result<Self> {
+        Ok(AcquiredSocket {
+            fd: AsyncFd::new(fd)?,
+            mtu,
+        })
+    }
+
+    pub fn mtu(&self) -> u16 {
+        self.mtu
+    }
+
+    /// Receives the next value, `None` once the other end is closed
+    pub async fn recv(&self) -> io::Result<Option<Vec<u8>>> {
+        let mut buffer = vec![0; self.mtu as usize];
+        loop {
+            let mut guard = self.fd.readable().await?;
+            let result = guard.try_io(|fd| {
+                let received = unsafe {
+                    libc::recv(
+                        fd.as_raw_fd(),
+                        buffer.as_mut_ptr() as *mut libc::c_void,
+                        buffer.len(),
+                        0,
+                    )
+                };
+                if received < 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                Ok(received as usize)
+            });
+            match result {
+                Ok(Ok(0)) => return Ok(None),
+                Ok(Ok(received)) => {
+                    buffer.truncate(received);
+                    return Ok(Some(buffer));
+                }
+                Ok(Err(err)) => return Err(err),
+                Err(_would_block) => continue,
+            }
+        }
+    }
+
+    /// Sends a value as one packet, the other end truncates it to its MTU
+    pub async fn send(&self, value: &[u8]) -> io::Result<()> {
+        loop {
+            let mut guard = self.fd.writable().await?;
+            let result = guard.try_io(|fd| {
+                let sent = unsafe {
+                    libc::send(
+                        fd.as_raw_fd(),
+                        value.as_ptr() as *const libc::c_void,
+                        value.len(),
+                        libc::MSG_NOSIGNAL,
+                    )
+                };
+                if sent < 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                Ok(())
+            });
+            match result {
+                Ok(result) => return result,
+                Err(_would_block) => continue,
+            }
+        }
+    }
+
+    /// Resolves once the other end is closed, discarding anything received meanwhile
+    pub async fn closed(&self) {
+        while let Ok(Some(_)) = self.recv().await {}
+    }
+
+    /// Every value received until the other end is closed
+    pub fn into_payloads(self) -> PayloadStream {
+        self.payloads_with(())
+    }
+
+    /// Like `into_payloads`, keeping `guard` alive as long as the stream is read from
+    pub(crate) fn payloads_with<G: Send + 'static>(self, guard: G) -> PayloadStream {
+        stream::unfold((self, guard), |(socket, guard)| async move {
+            match socket.recv().await {
+                Ok(Some(value)) => Some((value, (socket, guard))),
+                Ok(None) => None,
+                Err(err) => {
+                    log::warn!("Failed to receive from acquired socket: {:?}", err);
+                    None
+                }
+            }
+        })
+        .boxed()
+    }
+}
@@ -5,13 +5,14 @@ use dbus::{
 use std::{collections::HashMap, sync::Arc};
 
 use super::{
+    centrals::central_from_path,
     connection::Connection,
     constants::{
-        ADAPTER_IFACE, DBUS_OBJECTMANAGER_IFACE, DBUS_PROPERTIES_IFACE,
+        ADAPTER_IFACE, DBUS_OBJECTMANAGER_IFACE, DBUS_PROPERTIES_IFACE, DEVICE_IFACE,
         LE_ADVERTISING_MANAGER_IFACE,
     },
 };
-use crate::Error;
+use crate::{Bond, Error};
 
 #[derive(Debug, Clone)]
 pub struct Adapter {
@@ -94,4 +95,69 @@ impl Adapter {
             .await?;
         Ok(())
     }
+
+    pub async fn get_bonds(self: &Self) -> Result<Vec<Bond>, Error> {
+        let path = "/".into();
+        let proxy = self.connection.get_bluez_proxy(&path);
+
+        let (props,): (ManagedObjectsProps,) = proxy
+            .method_call(DBUS_OBJECTMANAGER_IFACE, "GetManagedObjects", ())
+            .await?;
+        let adapter_prefix = format!("{}/", self.object_path);
+        Ok(props
+            .into_iter()
+            .filter(|(path, _props)| path.starts_with(&adapter_prefix))
+            .filter_map(|(path, mut props)| {
+                let device = props.remove(DEVICE_IFACE)?;
+                let flag = |name: &str| {
+                    device
+                        .get(name)
+                        .and_then(|value| value.as_u64())
+                        .map(|value| value != 0)
+                };
+                let paired = flag("Paired").unwrap_or(false);
+                // `Bonded` is only reported by newer versions of BlueZ, which used to store the
+                // keys of every paired device
+                let bonded = flag("Bonded").unwrap_or(paired);
+                if !paired && !bonded {
+                    return None;
+                }
+                Some(Bond {
+                    central: central_from_path(&path),
+                    paired,
+                    bonded,
+                    trusted: flag("Trusted").unwrap_or(false),
+                    connected: flag("Connected").unwrap_or(false),
+                })
+            })
+            .collect())
+    }
+
+    pub async fn remove_device(self: &Self, device: &Path<'static>) -> Result<(), Error> {
+        let proxy = self.connection.get_bluez_proxy(&self.object_path);
+        proxy
+            .method_call::<(), _, _, _>(ADAPTER_IFACE, "RemoveDevice", (device,))
+            .await?;
+        Ok(())
+    }
+
+    pub async fn set_trusted(
+        self: &Self,
+        device: &Path<'static>,
+        trusted: bool,
+    ) -> Result<(), Error> {
+        let proxy = self.connection.get_bluez_proxy(device);
+        proxy
+            .method_call::<(), _, _, _>(
+                DBUS_PROPERTIES_IFACE,
+                "Set",
+                (
+                    DEVICE_IFACE,
+                    "Trusted",
+                    MessageItem::Variant(Box::new(trusted.into())),
+                ),
+            )
+            .await?;
+        Ok(())
+    }
 }
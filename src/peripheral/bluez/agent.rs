@@ -0,0 +1,232 @@
+use dbus::{
+    channel::{MatchingReceiver, Token},
+    message::MatchRule,
+    Path,
+};
+use dbus_crossroads::MethodErr;
+use futures::prelude::*;
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+use super::{
+    centrals::central_from_path,
+    common,
+    connection::Connection,
+    constants::{
+        AGENT_IFACE, AGENT_MANAGER_IFACE, AGENT_MANAGER_PATH, AGENT_PATH, BLUEZ_ERROR_CANCELED,
+        BLUEZ_ERROR_REJECTED,
+    },
+};
+use crate::{AgentError, AgentHandler, Error, IoCapability};
+
+fn agent_error(agent_error: AgentError) -> MethodErr {
+    match agent_error {
+        AgentError::Rejected => MethodErr::from((BLUEZ_ERROR_REJECTED, "")),
+        AgentError::Canceled => MethodErr::from((BLUEZ_ERROR_CANCELED, "")),
+    }
+}
+
+/// A pairing agent exported on D-Bus and registered with `org.bluez.AgentManager1`
+#[derive(Debug, Clone)]
+pub struct Agent {
+    connection: Arc<Connection>,
+    pub object_path: Path<'static>,
+    // Identifies the receiver dispatching BlueZ's method calls to the agent, kept as the number
+    // of its `Token`, which isn't `Debug`
+    receive_token: usize,
+}
+
+impl Agent {
+    /// Registers `handler` as the default agent on the system bus
+    pub async fn register<H: AgentHandler>(
+        io_capability: IoCapability,
+        handler: H,
+    ) -> Result<Self, Error> {
+        let connection = Arc::new(Connection::new()?);
+        Agent::register_with(connection, io_capability, handler).await
+    }
+
+    pub(crate) async fn register_with<H: AgentHandler>(
+        connection: Arc<Connection>,
+        io_capability: IoCapability,
+        handler: H,
+    ) -> Result<Self, Error> {
+        let handler: Arc<dyn AgentHandler> = Arc::new(handler);
+        let object_path: Path<'static> = AGENT_PATH.into();
+
+        let mut tree = common::Tree::new();
+        tree.set_async_support(Some((
+            connection.default.clone(),
+            Box::new(|x| {
+                tokio::spawn(x);
+            }),
+        )));
+
+        let iface_token = tree.register(AGENT_IFACE, |b| {
+            let release_handler = Arc::clone(&handler);
+            b.method("Release", (), (), move |_ctx, _cr, ()| {
+                release_handler.release();
+                Ok(())
+            });
+            let pin_code_handler = Arc::clone(&handler);
+            b.method_with_cr_async(
+                "RequestPinCode",
+                ("device",),
+                ("pincode",),
+                move |mut ctx, _cr, (device,): (Path<'static>,)| {
+                    pin_code_handler
+                        .request_pin_code(central_from_path(&device))
+                        .map(move |result| {
+                            ctx.reply(result.map(|pin_code| (pin_code,)).map_err(agent_error))
+                        })
+                },
+            );
+            let display_pin_code_handler = Arc::clone(&handler);
+            b.method_with_cr_async(
+                "DisplayPinCode",
+                ("device", "pincode"),
+                (),
+                move |mut ctx, _cr, (device, pin_code): (Path<'static>, String)| {
+                    display_pin_code_handler
+                        .display_pin_code(central_from_path(&device), pin_code)
+                        .map(move |result| ctx.reply(result.map_err(agent_error)))
+                },
+            );
+            let passkey_handler = Arc::clone(&handler);
+            b.method_with_cr_async(
+                "RequestPasskey",
+                ("device",),
+                ("passkey",),
+                move |mut ctx, _cr, (device,): (Path<'static>,)| {
+                    passkey_handler
+                        .request_passkey(central_from_path(&device))
+                        .map(move |result| {
+                            ctx.reply(result.map(|passkey| (passkey,)).map_err(agent_error))
+                        })
+                },
+            );
+            let display_passkey_handler = Arc::clone(&handler);
+            b.method_with_cr_async(
+                "DisplayPasskey",
+                ("device", "passkey", "entered"),
+                (),
+                move |mut ctx, _cr, (device, passkey, entered): (Path<'static>, u32, u16)| {
+                    display_passkey_handler
+                        .display_passkey(central_from_path(&device), passkey, entered)
+                        .map(move |result| ctx.reply(result.map_err(agent_error)))
+                },
+            );
+            let confirmation_handler = Arc::clone(&handler);
+            b.method_with_cr_async(
+                "RequestConfirmation",
+                ("device", "passkey"),
+                (),
+                move |mut ctx, _cr, (device, passkey): (Path<'static>, u32)| {
+                    confirmation_handler
+                        .request_confirmation(central_from_path(&device), passkey)
+                        .map(move |result| ctx.reply(result.map_err(agent_error)))
+                },
+            );
+            let authorization_handler = Arc::clone(&handler);
+            b.method_with_cr_async(
+                "RequestAuthorization",
+                ("device",),
+                (),
+                move |mut ctx, _cr, (device,): (Path<'static>,)| {
+                    authorization_handler
+                        .request_authorization(central_from_path(&device))
+                        .map(move |result| ctx.reply(result.map_err(agent_error)))
+                },
+            );
+            let authorize_service_handler = Arc::clone(&handler);
+            b.method_with_cr_async(
+                "AuthorizeService",
+                ("device", "uuid"),
+                (),
+                move |mut ctx, _cr, (device, uuid): (Path<'static>, String)| {
+                    let central = central_from_path(&device);
+                    let authorize = Uuid::parse_str(&uuid)
+                        .map(|uuid| authorize_service_handler.authorize_service(central, uuid))
+                        .unwrap_or_else(|_| Box::pin(future::err(AgentError::Rejected)));
+                    authorize.map(move |result| ctx.reply(result.map_err(agent_error)))
+                },
+            );
+            let cancel_handler = Arc::clone(&handler);
+            b.method("Cancel", (), (), move |_ctx, _cr, ()| {
+                cancel_handler.cancel();
+                Ok(())
+            });
+        });
+        tree.insert(object_path.clone(), &[iface_token], ());
+
+        let receive_token = {
+            let tree = Arc::new(Mutex::new(tree));
+            let mut match_rule = MatchRule::new_method_call();
+            match_rule.path = Some(object_path.clone());
+            connection
+                .default
+                .start_receive(
+                    match_rule,
+                    Box::new(move |msg, conn| {
+                        tree.lock().unwrap().handle_message(msg, conn).unwrap();
+                        true
+                    }),
+                )
+                .0
+        };
+
+        let agent = Agent {
+            connection,
+            object_path,
+            receive_token,
+        };
+
+        let manager_path = AGENT_MANAGER_PATH.into();
+        let proxy = agent.connection.get_bluez_proxy(&manager_path);
+        let io_capability: &str = io_capability.into();
+        let registered = async {
+            proxy
+                .method_call::<(), _, _, _>(
+                    AGENT_MANAGER_IFACE,
+                    "RegisterAgent",
+                    (&agent.object_path, io_capability),
+                )
+                .await?;
+            proxy
+                .method_call::<(), _, _, _>(
+                    AGENT_MANAGER_IFACE,
+                    "RequestDefaultAgent",
+                    (&agent.object_path,),
+                )
+                .await
+        }
+        .await;
+        if let Err(err) = registered {
+            agent
+                .connection
+                .default
+                .stop_receive(Token(agent.receive_token));
+            return Err(err.into());
+        }
+
+        Ok(agent)
+    }
+
+    /// Also stops dispatching method calls to the handler, so registering again doesn't leave
+    /// receivers behind
+    pub async fn unregister(&self) -> Result<(), Error> {
+        let manager_path = AGENT_MANAGER_PATH.into();
+        let proxy = self.connection.get_bluez_proxy(&manager_path);
+        let unregistered = proxy
+            .method_call::<(), _, _, _>(
+                AGENT_MANAGER_IFACE,
+                "UnregisterAgent",
+                (&self.object_path,),
+            )
+            .await;
+        self.connection
+            .default
+            .stop_receive(Token(self.receive_token));
+        unregistered.map_err(From::from)
+    }
+}
@@ -0,0 +1,183 @@
+use dbus::{
+    arg::{RefArg, Variant},
+    message::MatchRule,
+    nonblock::stdintf::org_freedesktop_dbus::PropertiesPropertiesChanged,
+    Path,
+};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use super::{
+    connection::Connection,
+    constants::{DBUS_PROPERTIES_IFACE, DEFAULT_MTU, DEVICE_IFACE},
+};
+use crate::{Central, Error, MtuChanged, PeripheralEvent, PeripheralEventSender};
+
+type OptionsMap = HashMap<String, Variant<Box<dyn RefArg>>>;
+
+/// Keeps track of the centrals BlueZ has told us about and their negotiated ATT MTU
+#[derive(Debug, Clone)]
+pub struct Centrals {
+    mtus: Arc<Mutex<HashMap<Central, u16>>>,
+    event_sender: Arc<Mutex<Option<PeripheralEventSender>>>,
+}
+
+impl Centrals {
+    pub async fn new(connection: &Arc<Connection>, adapter: &Path<'static>) -> Result<Self, Error> {
+        let centrals = Centrals {
+            mtus: Arc::new(Mutex::new(HashMap::new())),
+            event_sender: Arc::new(Mutex::new(None)),
+        };
+
+        // Forget the MTU of a central once it disconnects, the next connection negotiates again
+        let mut match_rule = MatchRule::new_signal(DBUS_PROPERTIES_IFACE, "PropertiesChanged");
+        match_rule.path = Some(adapter.clone());
+        match_rule.path_is_namespace = true;
+        let mtus = Arc::clone(&centrals.mtus);
+        connection.default.add_match(match_rule).await?.cb(
+            move |msg, changed: PropertiesPropertiesChanged| {
+                if changed.interface_name != DEVICE_IFACE {
+                    return true;
+                }
+                let disconnected = changed
+                    .changed_properties
+                    .get("Connected")
+                    .and_then(|connected| connected.as_u64())
+                    == Some(0);
+                if let (true, Some(path)) = (disconnected, msg.path()) {
+                    mtus.lock().unwrap().remove(&central_from_path(&path));
+                }
+                true
+            },
+        );
+
+        Ok(centrals)
+    }
+
+    pub fn set_event_sender(&self, event_sender: PeripheralEventSender) {
+        self.event_sender.lock().unwrap().replace(event_sender);
+    }
+
+    pub fn mtu(&self, central: &Central) -> Option<u16> {
+        self.mtus.lock().unwrap().get(central).cloned()
+    }
+
+    /// The MTU which is safe to use for every connected central, e.g. when sending notifications
+    /// through BlueZ which fans them out to all subscribers
+    pub fn min_mtu(&self) -> u16 {
+        self.mtus
+            .lock()
+            .unwrap()
+            .values()
+            .cloned()
+            .min()
+            .unwrap_or(DEFAULT_MTU)
+    }
+
+    /// Reads the `device` and `mtu` options BlueZ passes along with GATT method calls, recording
+    /// the MTU and emitting `MtuChanged` when it differs from the one last seen for the central
+    pub fn observe(&self, options: &OptionsMap) -> (Option<Central>, u16) {
+        let mtu = options
+            .get("mtu")
+            .and_then(RefArg::as_u64)
+            .map(|mtu| mtu as u16);
+        let central = options
+            .get("device")
+            .and_then(|device| device.as_str())
+            .map(central_from_path);
+
+        let (central, mtu) = match (central, mtu) {
+            (Some(central), Some(mtu)) => (central, mtu),
+            (Some(central), None) => {
+                let mtu = self.mtu(&central).unwrap_or(DEFAULT_MTU);
+                return (Some(central), mtu);
+            }
+            (None, mtu) => return (None, mtu.unwrap_or(DEFAULT_MTU)),
+        };
+
+        let previous = self.mtus.lock().unwrap().insert(central.clone(), mtu);
+        if previous != Some(mtu) {
+            self.emit(PeripheralEvent::MtuChanged(MtuChanged {
+                central: central.clone(),
+                mtu,
+            }));
+        }
+
+        (Some(central), mtu)
+    }
+
+    /// Never waits for the application, a full or dropped event receiver mustn't stall the GATT
+    /// requests BlueZ is waiting on, so the event is dropped instead
+    pub fn emit(&self, event: PeripheralEvent) {
+        if let Some(event_sender) = self.event_sender.lock().unwrap().as_mut() {
+            if let Err(err) = event_sender.try_send(event) {
+                log::warn!("Peripheral event not delivered: {:?}", err.into_inner());
+            }
+        }
+    }
+}
+
+/// BlueZ device paths look like `/org/bluez/hci0/dev_AA_BB_CC_DD_EE_FF`
+pub fn central_from_path(path: &str) -> Central {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    Central::new(name.trim_start_matches("dev_").replace('_', ":"))
+}
+
+pub fn path_from_central(adapter: &Path<'static>, central: &Central) -> Path<'static> {
+    format!("{}/dev_{}", adapter, central.address.replace(':', "_")).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::channel::mpsc;
+
+    use super::*;
+
+    fn options(device: &str, mtu: Option<u16>) -> OptionsMap {
+        let mut options: OptionsMap = HashMap::new();
+        options.insert(
+            "device".into(),
+            Variant(Box::new(Path::from(device.to_owned()))),
+        );
+        if let Some(mtu) = mtu {
+            options.insert("mtu".into(), Variant(Box::new(mtu)));
+        }
+        options
+    }
+
+    #[test]
+    fn it_tracks_the_mtu_per_central() {
+        let centrals = Centrals {
+            mtus: Arc::new(Mutex::new(HashMap::new())),
+            event_sender: Arc::new(Mutex::new(None)),
+        };
+        let (event_sender, mut events) = mpsc::channel(4);
+        centrals.set_event_sender(event_sender);
+        let first = Central::new("AA:BB:CC:DD:EE:FF");
+        let second = Central::new("00:11:22:33:44:55");
+
+        let observed =
+            centrals.observe(&options("/org/bluez/hci0/dev_AA_BB_CC_DD_EE_FF", Some(185)));
+        assert_eq!(observed, (Some(first.clone()), 185));
+        match events.try_next() {
+            Ok(Some(PeripheralEvent::MtuChanged(changed))) => {
+                assert_eq!((changed.central, changed.mtu), (first.clone(), 185));
+            }
+            event => panic!("Expected MtuChanged, got {:?}", event),
+        }
+
+        // The same MTU again isn't a change, and requests without one use the last seen
+        centrals.observe(&options("/org/bluez/hci0/dev_AA_BB_CC_DD_EE_FF", Some(185)));
+        let observed = centrals.observe(&options("/org/bluez/hci0/dev_AA_BB_CC_DD_EE_FF", None));
+        assert_eq!(observed, (Some(first.clone()), 185));
+        assert!(events.try_next().is_err());
+
+        let observed = centrals.observe(&options("/org/bluez/hci0/dev_00_11_22_33_44_55", None));
+        assert_eq!(observed, (Some(second.clone()), DEFAULT_MTU));
+        centrals.observe(&options("/org/bluez/hci0/dev_00_11_22_33_44_55", Some(64)));
+        assert_eq!(centrals.mtu(&second), Some(64));
+        assert_eq!(centrals.min_mtu(), 64);
+    }
+}
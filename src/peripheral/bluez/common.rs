@@ -1,5 +1,10 @@
+use dbus::{
+    arg::{RefArg, Variant},
+    nonblock::stdintf::org_freedesktop_dbus::PropertiesPropertiesChanged,
+    Message, Path,
+};
 use dbus_crossroads::Crossroads;
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 use crate::gatt;
 
@@ -36,3 +41,25 @@ impl GattDataType {
 }
 
 pub type Tree = Crossroads;
+
+pub fn properties_changed(
+    object_path: &Path,
+    interface_name: &str,
+    property: &str,
+    value: Box<dyn RefArg>,
+) -> Message {
+    let mut props = HashMap::new();
+    props.insert(property.to_owned(), Variant(value));
+    let signal = PropertiesPropertiesChanged {
+        interface_name: interface_name.to_string(),
+        changed_properties: props,
+        invalidated_properties: Vec::new(),
+    };
+    let mut signal_message = Message::signal(
+        object_path,
+        &"org.freedesktop.DBus.Properties".into(),
+        &"PropertiesChanged".into(),
+    );
+    signal_message.append_all(signal);
+    signal_message
+}
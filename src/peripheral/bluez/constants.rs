@@ -6,6 +6,11 @@ pub const DBUS_OBJECTMANAGER_IFACE: &str = "org.freedesktop.DBus.ObjectManager";
 pub const BLUEZ_SERVICE_NAME: &str = "org.bluez";
 
 pub const ADAPTER_IFACE: &str = "org.bluez.Adapter1";
+pub const DEVICE_IFACE: &str = "org.bluez.Device1";
+
+pub const AGENT_MANAGER_IFACE: &str = "org.bluez.AgentManager1";
+pub const AGENT_IFACE: &str = "org.bluez.Agent1";
+pub const AGENT_MANAGER_PATH: &str = "/org/bluez";
 
 pub const LE_ADVERTISING_MANAGER_IFACE: &str = "org.bluez.LEAdvertisingManager1";
 pub const LE_ADVERTISEMENT_IFACE: &str = "org.bluez.LEAdvertisement1";
@@ -16,12 +21,28 @@ pub const GATT_DESCRIPTOR_IFACE: &str = "org.bluez.GattDescriptor1";
 pub const GATT_GATT_MANAGER_IFACE: &str = "org.bluez.GattManager1";
 
 pub const BLUEZ_ERROR_FAILED: &str = "org.bluez.Error.Failed";
-// pub const BLUEZ_ERROR_INPROGRESS: &str = "org.bluez.Error.InProgress";
-// pub const BLUEZ_ERROR_NOTPERMITTED: &str = "org.bluez.Error.NotPermitted";
-// pub const BLUEZ_ERROR_NOTAUTHORIZED: &str = "org.bluez.Error.NotAuthorized";
-// pub const BLUEZ_ERROR_INVALIDOFFSET: &str = "org.bluez.Error.InvalidOffset";
+pub const BLUEZ_ERROR_INPROGRESS: &str = "org.bluez.Error.InProgress";
+pub const BLUEZ_ERROR_NOTPERMITTED: &str = "org.bluez.Error.NotPermitted";
+pub const BLUEZ_ERROR_NOTAUTHORIZED: &str = "org.bluez.Error.NotAuthorized";
+pub const BLUEZ_ERROR_INVALIDOFFSET: &str = "org.bluez.Error.InvalidOffset";
+pub const BLUEZ_ERROR_INVALIDVALUELENGTH: &str = "org.bluez.Error.InvalidValueLength";
 pub const BLUEZ_ERROR_NOTSUPPORTED: &str = "org.bluez.Error.NotSupported";
+pub const BLUEZ_ERROR_REJECTED: &str = "org.bluez.Error.Rejected";
+pub const BLUEZ_ERROR_CANCELED: &str = "org.bluez.Error.Canceled";
+// Not one BlueZ maps to an ATT error code, so it answers with Unlikely Error (0x0E)
+pub const BLUEZ_ERROR_UNLIKELY: &str = "org.bluez.Error.Unlikely";
 
 pub const PATH_BASE: &str = "/org/bluez/example";
+// Outside of `PATH_BASE`, which is claimed entirely by the GATT application
+pub const AGENT_PATH: &str = "/org/bluez/agent";
+
+// The ATT MTU of a connection before an exchange has taken place
+pub const DEFAULT_MTU: u16 = 23;
+
+// The longest value an attribute may hold
+pub const MAX_ATTRIBUTE_LENGTH: usize = 512;
+// The read blob requests of a long read also follow each other closely, a value kept longer is
+// considered stale
+pub const LONG_READ_TIMEOUT: Duration = Duration::from_secs(1);
 
 pub const BLUEZ_DBUS_TIMEOUT: Duration = Duration::from_secs(30);
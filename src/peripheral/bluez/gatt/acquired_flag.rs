@@ -0,0 +1,64 @@
+use dbus::{channel::Sender, Path};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use super::super::{common, constants::GATT_CHARACTERISTIC_IFACE, Connection};
+
+/// Mirrors whether a socket is acquired in the `WriteAcquired` or `NotifyAcquired` property
+#[derive(Debug, Clone)]
+pub struct AcquiredFlag {
+    connection: Arc<Connection>,
+    object_path: Path<'static>,
+    property: &'static str,
+    acquired: Arc<AtomicBool>,
+}
+
+impl AcquiredFlag {
+    pub fn new(
+        connection: &Arc<Connection>,
+        object_path: &Path<'static>,
+        property: &'static str,
+    ) -> Self {
+        AcquiredFlag {
+            connection: Arc::clone(connection),
+            object_path: object_path.clone(),
+            property,
+            acquired: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn get(&self) -> bool {
+        self.acquired.load(Ordering::SeqCst)
+    }
+
+    /// Returns a guard releasing the flag when dropped, unless it's already acquired
+    pub fn acquire(&self) -> Option<AcquiredGuard> {
+        self.acquired
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .ok()?;
+        self.changed(true);
+        Some(AcquiredGuard(self.clone()))
+    }
+
+    fn changed(&self, acquired: bool) {
+        let signal_message = common::properties_changed(
+            &self.object_path,
+            GATT_CHARACTERISTIC_IFACE,
+            self.property,
+            Box::new(acquired),
+        );
+        self.connection.default.send(signal_message).ok();
+    }
+}
+
+#[derive(Debug)]
+pub struct AcquiredGuard(AcquiredFlag);
+
+impl Drop for AcquiredGuard {
+    fn drop(&mut self) {
+        self.0.acquired.store(false, Ordering::SeqCst);
+        self.0.changed(false);
+    }
+}
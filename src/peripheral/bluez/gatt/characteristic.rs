@@ -1,217 +1,568 @@
 use dbus::{
-    arg::{RefArg, Variant},
-    channel::Sender,
-    nonblock::stdintf::org_freedesktop_dbus::PropertiesPropertiesChanged,
-    Message, Path,
+    arg::{self, RefArg, Variant},
+    Path,
 };
 use dbus_tree::MethodErr;
-use futures::{
-    channel::{mpsc, oneshot},
-    prelude::*,
+use futures::{future, prelude::*};
+use std::{
+    collections::HashMap,
+    os::unix::io::{FromRawFd, IntoRawFd},
+    sync::Arc,
 };
-use std::{collections::HashMap, sync::Arc};
+use tokio::sync::watch;
 
 use super::{
     super::{
+        acquired_socket::AcquiredSocket,
+        centrals::Centrals,
         common,
         common::GattDataType,
-        constants::{BLUEZ_ERROR_FAILED, BLUEZ_ERROR_NOTSUPPORTED, GATT_CHARACTERISTIC_IFACE},
+        constants::{
+            BLUEZ_ERROR_FAILED, BLUEZ_ERROR_INPROGRESS, BLUEZ_ERROR_NOTAUTHORIZED,
+            BLUEZ_ERROR_NOTSUPPORTED, GATT_CHARACTERISTIC_IFACE,
+        },
         Connection,
     },
+    acquired_flag::AcquiredFlag,
     flags::Flags,
+    handle::handle_property,
+    long_read::LongRead,
+    long_write::LongWrite,
+    notifier::{Notifier, Origin},
+    request,
+    service::Service,
 };
-use crate::{gatt, Error};
+use crate::{gatt, gatt::characteristic::ValueChanges, Central, Error};
 
 type OptionsMap = HashMap<String, Variant<Box<dyn RefArg>>>;
 
 #[derive(Debug, Clone)]
 pub struct Characteristic {
     pub object_path: Path<'static>,
+    pub attribute: gatt::Attribute,
 }
 
 impl Characteristic {
     pub fn new(
         connection: &Arc<Connection>,
+        centrals: &Centrals,
+        default_deadline: &request::DefaultDeadline,
         tree: &mut common::Tree,
         characteristic: &Arc<gatt::characteristic::Characteristic>,
-        service: &Path<'static>,
+        service: &Service,
         index: u64,
     ) -> Result<Self, Error> {
-        let object_path: Path = format!("{}/characteristic{:04}", service, index).into();
+        let object_path: Path =
+            format!("{}/characteristic{:04}", service.object_path, index).into();
         let object_path_data = common::GattDataType::Characteristic(Arc::clone(characteristic));
-        // Setup a channel for notifications
-        let (message_sender, message_receiver) = mpsc::channel(1);
-        {
-            let object_path = object_path.clone();
-            let connection = Arc::clone(connection);
-            tokio::spawn(
-                message_receiver
-                    .map(move |notification: Vec<u8>| {
-                        // For notifications, BlueZ wants a PropertiesChanged
-                        // signal on the optional `Value` property. It doesn't
-                        // require that the property actually exists.
-                        let mut props = HashMap::new();
-                        props.insert("Value".to_owned(), Variant(Box::new(notification) as _));
-                        let signal = PropertiesPropertiesChanged {
-                            interface_name: GATT_CHARACTERISTIC_IFACE.to_string(),
-                            changed_properties: props,
-                            invalidated_properties: Vec::new(),
-                        };
-                        let mut signal_message = Message::signal(
-                            &object_path,
-                            &"org.freedesktop.DBus.Properties".into(),
-                            &"PropertiesChanged".into(),
-                        );
-                        signal_message.append_all(signal);
-                        connection.default.send(signal_message).ok();
-                    })
-                    .collect::<()>(),
-            );
-        }
+        let notifier = Notifier::new(connection, &object_path);
+        let attribute = gatt::Attribute {
+            id: characteristic.id,
+            uuid: characteristic.uuid,
+            service: service.uuid,
+            characteristic: None,
+        };
 
         let iface_token = tree.register::<GattDataType, _, _>(GATT_CHARACTERISTIC_IFACE, |b| {
-            let message_sender = message_sender.clone();
+            let centrals_read = centrals.clone();
+            let default_deadline_read = default_deadline.clone();
+            let attribute_read = attribute.clone();
+            let long_read = LongRead::default();
             b.method_with_cr_async(
                 "ReadValue",
                 ("options",),
                 ("value",),
-                |mut ctx, cr, (options,): (OptionsMap,)| {
+                move |mut ctx, cr, (options,): (OptionsMap,)| {
                     let offset = options.get("offset").and_then(RefArg::as_u64).unwrap_or(0) as u16;
-                    let mtu = options.get("mtu").and_then(RefArg::as_u64).unwrap_or(23) as u16;
+                    let centrals = centrals_read.clone();
+                    let default_deadline = default_deadline_read.clone();
+                    let attribute = attribute_read.clone();
+                    let long_read = long_read.clone();
 
                     let characteristic = cr
                         .data_mut::<GattDataType>(ctx.path())
                         .unwrap()
                         .get_characteristic();
                     async move {
-                        let event_sender = characteristic
-                            .properties
-                            .read
-                            .clone()
-                            .ok_or_else(|| MethodErr::from((BLUEZ_ERROR_NOTSUPPORTED, "")))?;
-                        let (sender, receiver) = oneshot::channel();
-                        event_sender
-                            .sender()
-                            .send(gatt::event::Event::ReadRequest(gatt::event::ReadRequest {
+                        let (central, mtu) = centrals.observe(&options);
+                        let target = request::Target::new(
+                            &attribute,
+                            central,
+                            &characteristic.response_deadline,
+                            &default_deadline,
+                            &centrals,
+                        );
+                        // Also asked before serving a stored value
+                        if characteristic.authorize {
+                            let event_sender = characteristic
+                                .properties
+                                .authorization_sender()
+                                .ok_or_else(|| MethodErr::from((BLUEZ_ERROR_NOTAUTHORIZED, "")))?;
+                            request::authorize(
+                                event_sender,
+                                &target,
+                                gatt::event::Operation::Read,
                                 offset,
-                                response: sender,
                                 mtu,
-                            }))
+                            )
+                            .await?;
+                        }
+                        let event_sender = match characteristic.properties.read.clone() {
+                            Some(event_sender) => event_sender,
+                            None => {
+                                let value = characteristic.value.get().ok_or_else(|| {
+                                    MethodErr::from((BLUEZ_ERROR_NOTSUPPORTED, ""))
+                                })?;
+                                return request::read_value(&value, offset).map(|value| (value,));
+                            }
+                        };
+                        if characteristic.long_read_cache {
+                            return request::read_cached(
+                                &long_read,
+                                event_sender.sender(),
+                                &target,
+                                offset,
+                                mtu,
+                            )
                             .await
-                            .map_err(|_| MethodErr::from((BLUEZ_ERROR_FAILED, "")))?;
-                        receiver
+                            .map(|value| (value,));
+                        }
+                        request::read(event_sender.sender(), &target, offset, mtu)
                             .await
-                            .map_err(|_| MethodErr::from((BLUEZ_ERROR_FAILED, "")))
-                            .and_then(|resp| match resp {
-                                gatt::event::Response::Success(value) => Ok((value,)),
-                                _ => Err(MethodErr::from((BLUEZ_ERROR_FAILED, ""))),
-                            })
+                            .map(|value| (value,))
                     }
                     .map(move |result| ctx.reply(result))
                 },
             );
+            let centrals_write = centrals.clone();
+            let default_deadline_write = default_deadline.clone();
+            let attribute_write = attribute.clone();
+            let long_write = LongWrite::default();
             b.method_with_cr_async(
                 "WriteValue",
                 ("data", "options"),
                 ("value",),
-                |mut ctx, cr, (data, options): (Vec<u8>, OptionsMap)| {
+                move |mut ctx, cr, (data, options): (Vec<u8>, OptionsMap)| {
                     let offset = options.get("offset").and_then(RefArg::as_u64).unwrap_or(0) as u16;
+                    let prepare_authorize = options
+                        .get("prepare-authorize")
+                        .and_then(RefArg::as_u64)
+                        .unwrap_or(0)
+                        != 0;
+                    let write_type = options
+                        .get("type")
+                        .and_then(RefArg::as_str)
+                        .unwrap_or("")
+                        .to_owned();
+                    let centrals = centrals_write.clone();
+                    let default_deadline = default_deadline_write.clone();
+                    let attribute = attribute_write.clone();
+                    let long_write = long_write.clone();
                     let characteristic = cr
                         .data_mut::<GattDataType>(ctx.path())
                         .unwrap()
                         .get_characteristic();
                     async move {
-                        let event_sender = characteristic
-                            .properties
-                            .write
-                            .clone()
-                            .ok_or_else(|| MethodErr::from((BLUEZ_ERROR_NOTSUPPORTED, "")))?;
-                        let (sender, receiver) = oneshot::channel();
-                        event_sender
-                            .sender()
-                            .send(gatt::event::Event::WriteRequest(
-                                gatt::event::WriteRequest {
-                                    data,
+                        let (central, mtu) = centrals.observe(&options);
+                        if characteristic.properties.write.is_none()
+                            && characteristic.value_writes.is_none()
+                        {
+                            return Err(MethodErr::from((BLUEZ_ERROR_NOTSUPPORTED, "")));
+                        }
+                        let target = request::Target::new(
+                            &attribute,
+                            central,
+                            &characteristic.response_deadline,
+                            &default_deadline,
+                            &centrals,
+                        );
+                        // Also asked before writing to the watch channel
+                        if characteristic.authorize {
+                            let event_sender = characteristic
+                                .properties
+                                .authorization_sender()
+                                .ok_or_else(|| MethodErr::from((BLUEZ_ERROR_NOTAUTHORIZED, "")))?;
+                            request::authorize(
+                                event_sender,
+                                &target,
+                                gatt::event::Operation::Write,
+                                offset,
+                                mtu,
+                            )
+                            .await?;
+                        }
+                        // BlueZ asks separately before queueing a prepared write
+                        if prepare_authorize {
+                            return Ok((vec![],));
+                        }
+                        let event_sender = match characteristic.properties.write.clone() {
+                            Some(event_sender) => event_sender.sender(),
+                            None => {
+                                if let Some(sender) = &characteristic.value_writes {
+                                    request::write_value(sender, &data, offset)?;
+                                }
+                                return Ok((vec![],));
+                            }
+                        };
+                        // Every write but a command is a part of the value, one at offset 0 starts
+                        // a new value
+                        if characteristic.long_write_assembly {
+                            if write_type != "command" {
+                                return request::assemble(
+                                    &long_write,
+                                    event_sender,
+                                    &target,
+                                    &data,
                                     offset,
-                                    without_response: false,
-                                    response: sender,
-                                },
-                            ))
-                            .await
-                            .map_err(|_| MethodErr::from((BLUEZ_ERROR_FAILED, "")))?;
-                        receiver
-                            .await
-                            .map_err(|_| MethodErr::from((BLUEZ_ERROR_FAILED, "")))
-                            .and_then(|resp| match resp {
-                                gatt::event::Response::Success(value) => Ok((value,)),
-                                _ => Err(MethodErr::from((BLUEZ_ERROR_FAILED, ""))),
-                            })
+                                    mtu,
+                                )
+                                .await
+                                .map(|()| (vec![],));
+                            }
+                            long_write.clear(&target.central);
+                        }
+                        request::write(
+                            event_sender,
+                            &target,
+                            data,
+                            offset,
+                            write_type == "command",
+                            mtu,
+                        )
+                        .await
+                        .map(|value| (value,))
                     }
                     .map(move |result| ctx.reply(result))
                 },
             );
+            if characteristic.acquire_write {
+                let centrals_acquire = centrals.clone();
+                let write_acquired = AcquiredFlag::new(connection, &object_path, "WriteAcquired");
+                let write_acquired_property = write_acquired.clone();
+                let attribute_acquire = attribute.clone();
+                b.method_with_cr_async(
+                    "AcquireWrite",
+                    ("options",),
+                    ("fd", "mtu"),
+                    move |mut ctx, cr, (options,): (OptionsMap,)| {
+                        let centrals = centrals_acquire.clone();
+                        let write_acquired = write_acquired.clone();
+                        let attribute = attribute_acquire.clone();
+                        let characteristic = cr
+                            .data_mut::<GattDataType>(ctx.path())
+                            .unwrap()
+                            .get_characteristic();
+                        async move {
+                            let (central, mtu) = centrals.observe(&options);
+                            let mut event_sender = characteristic
+                                .properties
+                                .write
+                                .clone()
+                                .ok_or_else(|| MethodErr::from((BLUEZ_ERROR_NOTSUPPORTED, "")))?
+                                .sender();
+                            let guard = write_acquired
+                                .acquire()
+                                .ok_or_else(|| MethodErr::from((BLUEZ_ERROR_INPROGRESS, "")))?;
+                            let (socket, fd) = AcquiredSocket::pair(mtu).map_err(|err| {
+                                MethodErr::from((BLUEZ_ERROR_FAILED, err.to_string()))
+                            })?;
+                            event_sender
+                                .send(gatt::event::Event::WriteAcquired(
+                                    gatt::event::WriteAcquired {
+                                        attribute,
+                                        central,
+                                        mtu,
+                                        payloads: socket.payloads_with(guard),
+                                    },
+                                ))
+                                .await
+                                .map_err(|_| MethodErr::from((BLUEZ_ERROR_FAILED, "")))?;
+                            let fd = unsafe { arg::OwnedFd::from_raw_fd(fd.into_raw_fd()) };
+                            Ok((fd, mtu))
+                        }
+                        .map(move |result| ctx.reply(result))
+                    },
+                );
+                b.property("WriteAcquired")
+                    .get(move |_ctx, _data| Ok(write_acquired_property.get()));
+            }
+            if characteristic.acquire_notify {
+                let centrals_acquire = centrals.clone();
+                let notifier_acquire = notifier.clone();
+                let notify_acquired = AcquiredFlag::new(connection, &object_path, "NotifyAcquired");
+                let notify_acquired_property = notify_acquired.clone();
+                let attribute_acquire = attribute.clone();
+                b.method_with_cr_async(
+                    "AcquireNotify",
+                    ("options",),
+                    ("fd", "mtu"),
+                    move |mut ctx, cr, (options,): (OptionsMap,)| {
+                        let centrals = centrals_acquire.clone();
+                        let notify_acquired = notify_acquired.clone();
+                        let notifier = notifier_acquire.clone();
+                        let attribute = attribute_acquire.clone();
+                        let characteristic = cr
+                            .data_mut::<GattDataType>(ctx.path())
+                            .unwrap()
+                            .get_characteristic();
+                        async move {
+                            let (central, mtu) = centrals.observe(&options);
+                            let guard = notify_acquired
+                                .acquire()
+                                .ok_or_else(|| MethodErr::from((BLUEZ_ERROR_INPROGRESS, "")))?;
+                            let (socket, fd) = AcquiredSocket::pair(mtu).map_err(|err| {
+                                MethodErr::from((BLUEZ_ERROR_FAILED, err.to_string()))
+                            })?;
+                            subscribe(
+                                &characteristic,
+                                &attribute,
+                                &notifier,
+                                Origin::AcquireNotify,
+                                central,
+                                mtu,
+                            )
+                            .await?;
+                            // BlueZ closes the socket instead of calling StopNotify
+                            tokio::spawn(async move {
+                                notifier.acquire(socket).await;
+                                drop(guard);
+                                unsubscribe(
+                                    &characteristic,
+                                    &attribute,
+                                    &notifier,
+                                    Origin::AcquireNotify,
+                                )
+                                .await
+                                .ok();
+                            });
+                            let fd = unsafe { arg::OwnedFd::from_raw_fd(fd.into_raw_fd()) };
+                            Ok((fd, mtu))
+                        }
+                        .map(move |result| ctx.reply(result))
+                    },
+                );
+                b.property("NotifyAcquired")
+                    .get(move |_ctx, _data| Ok(notify_acquired_property.get()));
+            }
+            let centrals_notify = centrals.clone();
+            let notifier_notify = notifier.clone();
+            let attribute_notify = attribute.clone();
             b.method_with_cr_async("StartNotify", (), (), move |mut ctx, cr, ()| {
                 let characteristic = cr
                     .data_mut::<GattDataType>(ctx.path())
                     .unwrap()
                     .get_characteristic();
-                let message_sender = message_sender.clone();
+                let notifier = notifier_notify.clone();
+                let attribute = attribute_notify.clone();
+                // StartNotify carries no options, so use the MTU every connected central can take
+                let mtu = centrals_notify.min_mtu();
                 async move {
-                    let (sender, mut receiver) = mpsc::channel(1);
-                    let notify_subscribe = gatt::event::NotifySubscribe {
-                        notification: sender,
-                    };
-                    tokio::spawn(async move {
-                        while let Some(notification) = receiver.next().await {
-                            let mut message_sender = message_sender.clone();
-                            let _ = message_sender.send(notification).await;
-                        }
-                    });
-                    let mut event_sender = characteristic
-                        .properties
-                        .notify
-                        .clone()
-                        .or_else(|| characteristic.properties.indicate.clone())
-                        .ok_or_else(|| MethodErr::from((BLUEZ_ERROR_NOTSUPPORTED, "")))?;
-                    event_sender
-                        .send(gatt::event::Event::NotifySubscribe(notify_subscribe))
-                        .await
-                        .map_err(|_| MethodErr::from((BLUEZ_ERROR_FAILED, "")))
-                        .map(|_| ())
+                    subscribe(
+                        &characteristic,
+                        &attribute,
+                        &notifier,
+                        Origin::StartNotify,
+                        None,
+                        mtu,
+                    )
+                    .await
                 }
                 .map(move |result| ctx.reply(result))
             });
-            b.method_with_cr_async("StopNotify", (), (), |mut ctx, cr, ()| {
+            let notifier_stop = notifier.clone();
+            let attribute_stop = attribute.clone();
+            b.method_with_cr_async("StopNotify", (), (), move |mut ctx, cr, ()| {
                 let characteristic = cr
                     .data_mut::<GattDataType>(ctx.path())
                     .unwrap()
                     .get_characteristic();
+                let notifier = notifier_stop.clone();
+                let attribute = attribute_stop.clone();
                 async move {
-                    let mut event_sender = characteristic
-                        .properties
-                        .notify
-                        .clone()
-                        .or_else(|| characteristic.properties.indicate.clone())
-                        .ok_or_else(|| MethodErr::from((BLUEZ_ERROR_NOTSUPPORTED, "")))?;
-                    event_sender
-                        .send(gatt::event::Event::NotifyUnsubscribe)
-                        .await
-                        .map_err(|_| MethodErr::from((BLUEZ_ERROR_FAILED, "")))
-                        .map(|_| ())
+                    unsubscribe(&characteristic, &attribute, &notifier, Origin::StartNotify).await
                 }
                 .map(move |result| ctx.reply(result))
             });
+            if characteristic.properties.indicate.is_some() {
+                b.method("Confirm", (), (), move |_ctx, _data, ()| {
+                    notifier.confirm();
+                    Ok(())
+                });
+            }
+            handle_property(b, characteristic.handle);
             b.property("UUID")
                 .get(|_ctx, data| Ok(data.get_characteristic().uuid.to_string()));
-            let service = service.clone();
+            let service = service.object_path.clone();
             b.property("Service")
                 .get(move |_ctx, _data| Ok(service.clone()));
             b.property("Flags")
-                .get(move |_ctx, data| Ok(data.get_characteristic().properties.flags()));
+                .get(move |_ctx, data| Ok(data.get_characteristic().flags()));
         });
 
         tree.insert(object_path.clone(), &[iface_token], object_path_data);
 
-        Ok(Characteristic { object_path })
+        Ok(Characteristic {
+            object_path,
+            attribute,
+        })
+    }
+}
+
+fn has_subscriptions(
+    characteristic: &gatt::characteristic::Characteristic,
+) -> Result<(), MethodErr> {
+    let properties = &characteristic.properties;
+    if properties.notify.is_none()
+        && properties.indicate.is_none()
+        && characteristic.value_changes.is_none()
+    {
+        return Err(MethodErr::from((BLUEZ_ERROR_NOTSUPPORTED, "")));
+    }
+    Ok(())
+}
+
+async fn subscribe(
+    characteristic: &gatt::characteristic::Characteristic,
+    attribute: &gatt::Attribute,
+    notifier: &Notifier,
+    origin: Origin,
+    central: Option<Central>,
+    mtu: u16,
+) -> Result<(), MethodErr> {
+    has_subscriptions(characteristic)?;
+
+    // BlueZ doesn't tell which of the two the central enabled, so a characteristic supporting
+    // both only tells its `notify` handler
+    if let Some(mut event_sender) = characteristic.properties.notify.clone() {
+        let notification = notifier.subscribe(
+            origin,
+            characteristic.notification_queue,
+            |notifier, notification| async move { notifier.notify(notification).await },
+        );
+        characteristic
+            .subscribers
+            .insert(central.clone(), notification.clone());
+        event_sender
+            .send(gatt::event::Event::NotifySubscribe(
+                gatt::event::NotifySubscribe {
+                    attribute: attribute.clone(),
+                    central: central.clone(),
+                    notification,
+                    mtu,
+                },
+            ))
+            .await
+            .map_err(|_| MethodErr::from((BLUEZ_ERROR_FAILED, "")))?;
+    } else if let Some(mut event_sender) = characteristic.properties.indicate.clone() {
+        let subscription = notifier.subscribe(
+            origin,
+            characteristic.notification_queue,
+            move |notifier, indication| async move { notifier.indicate(origin, indication).await },
+        );
+        event_sender
+            .send(gatt::event::Event::IndicateSubscribe(
+                gatt::event::IndicateSubscribe {
+                    attribute: attribute.clone(),
+                    central: central.clone(),
+                    indication: gatt::event::Indicator::new(
+                        subscription,
+                        characteristic.indication_timeout,
+                    ),
+                    mtu,
+                },
+            ))
+            .await
+            .map_err(|_| MethodErr::from((BLUEZ_ERROR_FAILED, "")))?;
+    }
+
+    if let (Some(changes), Some(receiver)) =
+        (characteristic.value_changes, characteristic.value.changes())
+    {
+        match changes {
+            ValueChanges::Notify => {
+                let notification = notifier.subscribe(
+                    origin,
+                    characteristic.notification_queue,
+                    |notifier, notification| async move { notifier.notify(notification).await },
+                );
+                characteristic
+                    .subscribers
+                    .insert(central, notification.clone());
+                tokio::spawn(relay_changes(
+                    receiver,
+                    notification.closed(),
+                    move |value| {
+                        let notification = notification.clone();
+                        async move {
+                            if let Err(err) = notification.send(value).await {
+                                log::warn!("Change not notified: {}", err);
+                            }
+                        }
+                    },
+                ));
+            }
+            ValueChanges::Indicate => {
+                let subscription = notifier.subscribe(
+                    origin,
+                    characteristic.notification_queue,
+                    move |notifier, indication| async move {
+                        notifier.indicate(origin, indication).await
+                    },
+                );
+                let indicator =
+                    gatt::event::Indicator::new(subscription, characteristic.indication_timeout);
+                tokio::spawn(relay_changes(receiver, indicator.closed(), move |value| {
+                    let indicator = indicator.clone();
+                    async move {
+                        if let Err(err) = indicator.indicate(value).await {
+                            log::warn!("Change not indicated: {}", err);
+                        }
+                    }
+                }));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Hands every value sent on the channel of `receiver` to `send`, until `closed` resolves
+async fn relay_changes<F, Fut>(
+    mut receiver: watch::Receiver<Vec<u8>>,
+    closed: impl Future<Output = ()>,
+    send: F,
+) where
+    F: Fn(Vec<u8>) -> Fut,
+    Fut: Future<Output = ()>,
+{
+    let relaying = async {
+        while receiver.changed().await.is_ok() {
+            let value = receiver.borrow_and_update().clone();
+            send(value).await;
+        }
+    };
+    futures::pin_mut!(relaying, closed);
+    future::select(relaying, closed).await;
+}
+
+async fn unsubscribe(
+    characteristic: &gatt::characteristic::Characteristic,
+    attribute: &gatt::Attribute,
+    notifier: &Notifier,
+    origin: Origin,
+) -> Result<(), MethodErr> {
+    has_subscriptions(characteristic)?;
+    notifier.unsubscribe(origin);
+
+    if let Some(mut event_sender) = characteristic.properties.notify.clone() {
+        event_sender
+            .send(gatt::event::Event::NotifyUnsubscribe(attribute.clone()))
+            .await
+            .map_err(|_| MethodErr::from((BLUEZ_ERROR_FAILED, "")))?;
+    } else if let Some(mut event_sender) = characteristic.properties.indicate.clone() {
+        event_sender
+            .send(gatt::event::Event::IndicateUnsubscribe(attribute.clone()))
+            .await
+            .map_err(|_| MethodErr::from((BLUEZ_ERROR_FAILED, "")))?;
     }
+    Ok(())
 }
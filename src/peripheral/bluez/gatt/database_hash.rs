@@ -0,0 +1,80 @@
+use dbus::Path;
+
+use super::flags::Flags;
+use crate::gatt::service::Service;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// 64-bit FNV-1a, which unlike `DefaultHasher` gives the same hash on every Rust release, so
+/// hashes can be compared across restarts and firmware versions
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn new() -> Self {
+        Fnv1a(FNV_OFFSET_BASIS)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    // Ends with a byte no string holds, so consecutive strings can't run into each other
+    fn write_str(&mut self, value: &str) {
+        self.write(value.as_bytes());
+        self.write(&[0xFF]);
+    }
+
+    fn write_handle(&mut self, handle: Option<u16>) {
+        match handle {
+            Some(handle) => {
+                self.write(&[1]);
+                self.write(&handle.to_le_bytes());
+            }
+            None => self.write(&[0]),
+        }
+    }
+
+    fn write_flags(&mut self, flags: Vec<String>) {
+        self.write(&(flags.len() as u32).to_le_bytes());
+        for flag in flags.iter() {
+            self.write_str(flag);
+        }
+    }
+}
+
+/// Hashes what centrals see of a service: the UUIDs, flags and handles of the service and its
+/// attributes, in registration order
+pub fn service_hash(service: &Service, includes: &[Path<'static>]) -> u64 {
+    let mut hasher = Fnv1a::new();
+    hasher.write(service.uuid.as_bytes());
+    hasher.write(&[service.primary as u8]);
+    hasher.write_handle(service.handle);
+    hasher.write(&(includes.len() as u32).to_le_bytes());
+    for include in includes {
+        hasher.write_str(include);
+    }
+    for characteristic in service.characteristics.iter() {
+        hasher.write(characteristic.uuid.as_bytes());
+        hasher.write_flags(characteristic.flags());
+        hasher.write_handle(characteristic.handle);
+        for descriptor in characteristic.descriptors.iter() {
+            hasher.write(descriptor.uuid.as_bytes());
+            hasher.write_flags(descriptor.flags());
+            hasher.write_handle(descriptor.handle);
+        }
+    }
+    hasher.0
+}
+
+/// Combines the hashes of every service, ordered the way they were added
+pub fn database_hash<I: IntoIterator<Item = u64>>(service_hashes: I) -> u64 {
+    let mut hasher = Fnv1a::new();
+    for service_hash in service_hashes {
+        hasher.write(&service_hash.to_le_bytes());
+    }
+    hasher.0
+}
@@ -3,16 +3,22 @@ use dbus::{
     Path,
 };
 use dbus_crossroads::MethodErr;
-use futures::{channel::oneshot, prelude::*};
+use futures::prelude::*;
 use std::{collections::HashMap, sync::Arc};
 
 use super::{
     super::{
+        centrals::Centrals,
         common,
         common::GattDataType,
-        constants::{BLUEZ_ERROR_FAILED, BLUEZ_ERROR_NOTSUPPORTED, GATT_DESCRIPTOR_IFACE},
+        constants::{BLUEZ_ERROR_NOTAUTHORIZED, BLUEZ_ERROR_NOTSUPPORTED, GATT_DESCRIPTOR_IFACE},
     },
+    characteristic::Characteristic,
     flags::Flags,
+    handle::handle_property,
+    long_read::LongRead,
+    long_write::LongWrite,
+    request,
 };
 use crate::{gatt, Error};
 
@@ -25,101 +31,189 @@ pub struct Descriptor {
 
 impl Descriptor {
     pub fn new(
+        centrals: &Centrals,
+        default_deadline: &request::DefaultDeadline,
         tree: &mut common::Tree,
         descriptor: &Arc<gatt::descriptor::Descriptor>,
-        characteristic: &Path<'static>,
+        characteristic: &Characteristic,
         index: u64,
     ) -> Result<Self, Error> {
+        let attribute = gatt::Attribute {
+            id: descriptor.id,
+            uuid: descriptor.uuid,
+            service: characteristic.attribute.service,
+            characteristic: Some(characteristic.attribute.id),
+        };
         // Setup value property for read / write by other methods
         let iface_token = tree.register::<GattDataType, _, _>(GATT_DESCRIPTOR_IFACE, |b| {
+            let centrals_read = centrals.clone();
+            let default_deadline_read = default_deadline.clone();
+            let attribute_read = attribute.clone();
+            let long_read = LongRead::default();
             b.method_with_cr_async(
                 "ReadValue",
                 ("options",),
                 ("value",),
-                |mut ctx, cr, (options,): (OptionsMap,)| {
+                move |mut ctx, cr, (options,): (OptionsMap,)| {
                     let offset = options.get("offset").and_then(RefArg::as_u64).unwrap_or(0) as u16;
-                    let mtu = options.get("mtu").and_then(RefArg::as_u64).unwrap_or(23) as u16;
+                    let centrals = centrals_read.clone();
+                    let default_deadline = default_deadline_read.clone();
+                    let attribute = attribute_read.clone();
+                    let long_read = long_read.clone();
                     let descriptor = cr
                         .data_mut::<GattDataType>(ctx.path())
                         .unwrap()
                         .get_descriptor();
                     async move {
-                        let event_sender = descriptor
-                            .properties
-                            .read
-                            .clone()
-                            .ok_or_else(|| MethodErr::from((BLUEZ_ERROR_NOTSUPPORTED, "")))?;
-                        let (sender, receiver) = oneshot::channel();
-                        event_sender
-                            .sender()
-                            .send(gatt::event::Event::ReadRequest(gatt::event::ReadRequest {
+                        let (central, mtu) = centrals.observe(&options);
+                        let target = request::Target::new(
+                            &attribute,
+                            central,
+                            &descriptor.response_deadline,
+                            &default_deadline,
+                            &centrals,
+                        );
+                        // Also asked before serving a stored value
+                        if descriptor.authorize {
+                            let event_sender = descriptor
+                                .properties
+                                .authorization_sender()
+                                .ok_or_else(|| MethodErr::from((BLUEZ_ERROR_NOTAUTHORIZED, "")))?;
+                            request::authorize(
+                                event_sender,
+                                &target,
+                                gatt::event::Operation::Read,
+                                offset,
+                                mtu,
+                            )
+                            .await?;
+                        }
+                        let event_sender = match descriptor.properties.read.clone() {
+                            Some(event_sender) => event_sender,
+                            None => {
+                                let value = descriptor.value.get().ok_or_else(|| {
+                                    MethodErr::from((BLUEZ_ERROR_NOTSUPPORTED, ""))
+                                })?;
+                                return request::read_value(&value, offset).map(|value| (value,));
+                            }
+                        };
+                        if descriptor.long_read_cache {
+                            return request::read_cached(
+                                &long_read,
+                                event_sender.sender(),
+                                &target,
                                 offset,
-                                response: sender,
                                 mtu,
-                            }))
+                            )
                             .await
-                            .map_err(|_| MethodErr::from((BLUEZ_ERROR_FAILED, "")))?;
-                        receiver
+                            .map(|value| (value,));
+                        }
+                        request::read(event_sender.sender(), &target, offset, mtu)
                             .await
-                            .map_err(|_| MethodErr::from((BLUEZ_ERROR_FAILED, "")))
-                            .and_then(|resp| match resp {
-                                gatt::event::Response::Success(value) => Ok((value,)),
-                                _ => Err(MethodErr::from((BLUEZ_ERROR_FAILED, ""))),
-                            })
+                            .map(|value| (value,))
                     }
                     .map(move |result| ctx.reply(result))
                 },
             );
+            let centrals_write = centrals.clone();
+            let default_deadline_write = default_deadline.clone();
+            let attribute_write = attribute.clone();
+            let long_write = LongWrite::default();
             b.method_with_cr_async(
                 "WriteValue",
                 ("data", "options"),
                 ("value",),
-                |mut ctx, cr, (data, options): (Vec<u8>, OptionsMap)| {
+                move |mut ctx, cr, (data, options): (Vec<u8>, OptionsMap)| {
                     let offset = options.get("offset").and_then(RefArg::as_u64).unwrap_or(0) as u16;
+                    let prepare_authorize = options
+                        .get("prepare-authorize")
+                        .and_then(RefArg::as_u64)
+                        .unwrap_or(0)
+                        != 0;
+                    let write_type = options
+                        .get("type")
+                        .and_then(RefArg::as_str)
+                        .unwrap_or("")
+                        .to_owned();
+                    let centrals = centrals_write.clone();
+                    let default_deadline = default_deadline_write.clone();
+                    let attribute = attribute_write.clone();
+                    let long_write = long_write.clone();
                     let descriptor = cr
                         .data_mut::<GattDataType>(ctx.path())
                         .unwrap()
                         .get_descriptor();
                     async move {
+                        let (central, mtu) = centrals.observe(&options);
                         let event_sender = descriptor
                             .properties
                             .write
                             .clone()
                             .ok_or_else(|| MethodErr::from((BLUEZ_ERROR_NOTSUPPORTED, "")))?;
-                        let (sender, receiver) = oneshot::channel();
-                        event_sender
-                            .sender()
-                            .send(gatt::event::Event::WriteRequest(
-                                gatt::event::WriteRequest {
-                                    data,
+                        let target = request::Target::new(
+                            &attribute,
+                            central,
+                            &descriptor.response_deadline,
+                            &default_deadline,
+                            &centrals,
+                        );
+                        if descriptor.authorize {
+                            request::authorize(
+                                event_sender.clone().sender(),
+                                &target,
+                                gatt::event::Operation::Write,
+                                offset,
+                                mtu,
+                            )
+                            .await?;
+                        }
+                        // BlueZ asks separately before queueing a prepared write
+                        if prepare_authorize {
+                            return Ok((vec![],));
+                        }
+                        let event_sender = event_sender.sender();
+                        // Every write but a command is a part of the value, one at offset 0 starts
+                        // a new value
+                        if descriptor.long_write_assembly {
+                            if write_type != "command" {
+                                return request::assemble(
+                                    &long_write,
+                                    event_sender,
+                                    &target,
+                                    &data,
                                     offset,
-                                    without_response: false,
-                                    response: sender,
-                                },
-                            ))
-                            .await
-                            .map_err(|_| MethodErr::from((BLUEZ_ERROR_FAILED, "")))?;
-                        receiver
-                            .await
-                            .map_err(|_| MethodErr::from((BLUEZ_ERROR_FAILED, "")))
-                            .and_then(|resp| match resp {
-                                gatt::event::Response::Success(value) => Ok((value,)),
-                                _ => Err(MethodErr::from((BLUEZ_ERROR_FAILED, ""))),
-                            })
+                                    mtu,
+                                )
+                                .await
+                                .map(|()| (vec![],));
+                            }
+                            long_write.clear(&target.central);
+                        }
+                        request::write(
+                            event_sender,
+                            &target,
+                            data,
+                            offset,
+                            write_type == "command",
+                            mtu,
+                        )
+                        .await
+                        .map(|value| (value,))
                     }
                     .map(move |result| ctx.reply(result))
                 },
             );
+            handle_property(b, descriptor.handle);
             b.property("UUID")
                 .get(|_ctx, data| Ok(data.get_descriptor().uuid.to_string()));
-            let characteristic = characteristic.clone();
+            let characteristic = characteristic.object_path.clone();
             b.property("Characteristic")
                 .get(move |_ctx, _data| Ok(characteristic.clone()));
             b.property("Flags")
-                .get(move |_ctx, data| Ok(data.get_descriptor().properties.flags()));
+                .get(move |_ctx, data| Ok(data.get_descriptor().flags()));
         });
         let object_path: Path =
-            format!("{}/descriptor{:04}", characteristic.to_string(), index).into();
+            format!("{}/descriptor{:04}", characteristic.object_path, index).into();
         let object_path_data = common::GattDataType::Descriptor(Arc::clone(descriptor));
 
         tree.insert(object_path.clone(), &[iface_token], object_path_data);
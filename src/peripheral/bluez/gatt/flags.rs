@@ -1,34 +1,49 @@
 use crate::gatt::{
-    characteristic::{self, Properties as CharacteristicProperties},
-    descriptor::{self, Properties as DescriptorProperties},
+    characteristic::{self, Characteristic, Properties as CharacteristicProperties, ValueChanges},
+    descriptor::{Descriptor, Properties as DescriptorProperties},
+    SecurityLevel, StoredValue,
 };
 
 pub trait Flags {
     fn flags(self: &Self) -> Vec<String>;
 }
 
+fn read_flag(security_level: SecurityLevel) -> &'static str {
+    match security_level {
+        SecurityLevel::None => "read",
+        SecurityLevel::Encrypted => "encrypt-read",
+        SecurityLevel::Authenticated => "encrypt-authenticated-read",
+        SecurityLevel::SecureConnections => "secure-read",
+    }
+}
+
+fn write_flag(security_level: SecurityLevel) -> &'static str {
+    match security_level {
+        SecurityLevel::None => "write",
+        SecurityLevel::Encrypted => "encrypt-write",
+        SecurityLevel::Authenticated => "encrypt-authenticated-write",
+        SecurityLevel::SecureConnections => "secure-write",
+    }
+}
+
 impl Flags for CharacteristicProperties {
     fn flags(self: &Self) -> Vec<String> {
         let mut flags = vec![];
         if let Some(ref read) = self.read {
-            let read_flags: &[&str] = match read.0 {
-                characteristic::Secure::Secure(_) => &["secure-read", "encrypt-authenticated-read"],
-                characteristic::Secure::Insecure(_) => &["read"],
-            };
-            flags.extend_from_slice(read_flags);
+            flags.push(read_flag(read.security_level()));
         }
 
         if let Some(ref write) = self.write {
-            let write_flag: &[&str] = match write {
-                characteristic::Write::WithResponse(secure) => match secure {
-                    characteristic::Secure::Secure(_) => {
-                        &["secure-write", "encrypt-authenticated-write"]
-                    }
-                    characteristic::Secure::Insecure(_) => &["write"],
-                },
-                characteristic::Write::WithoutResponse(_) => &["write-without-response"],
+            match write {
+                characteristic::Write::WithResponse(secure) => {
+                    flags.push(write_flag(secure.security_level()))
+                }
+                characteristic::Write::WithoutResponse(_) => flags.push("write-without-response"),
+                characteristic::Write::WithAndWithoutResponse(secure) => {
+                    flags.push(write_flag(secure.security_level()));
+                    flags.push("write-without-response");
+                }
             };
-            flags.extend_from_slice(write_flag);
         }
 
         if self.notify.is_some() {
@@ -47,21 +62,60 @@ impl Flags for DescriptorProperties {
     fn flags(self: &Self) -> Vec<String> {
         let mut flags = vec![];
         if let Some(ref read) = self.read {
-            let read_flags: &[&str] = match read.0 {
-                descriptor::Secure::Secure(_) => &["secure-read", "encrypt-authenticated-read"],
-                descriptor::Secure::Insecure(_) => &["read"],
-            };
-            flags.extend_from_slice(read_flags);
+            flags.push(read_flag(read.security_level()));
         }
 
         if let Some(ref write) = self.write {
-            let write_flags: &[&str] = match write.0 {
-                descriptor::Secure::Secure(_) => &["secure-write", "encrypt-authenticated-write"],
-                descriptor::Secure::Insecure(_) => &["write"],
-            };
-            flags.extend_from_slice(write_flags);
+            flags.push(write_flag(write.security_level()));
         }
 
         flags.iter().map(|s| String::from(*s)).collect()
     }
 }
+
+// Stored values are served by bluster itself, so they're readable without a handler
+fn stored_value_flags(flags: &mut Vec<String>, has_read: bool, value: &StoredValue) {
+    if !has_read && value.get().is_some() {
+        flags.insert(0, String::from(read_flag(SecurityLevel::None)));
+    }
+}
+
+impl Flags for Characteristic {
+    fn flags(self: &Self) -> Vec<String> {
+        let mut flags = self.properties.flags();
+        stored_value_flags(&mut flags, self.properties.read.is_some(), &self.value);
+        if self.properties.write.is_none() && self.value_writes.is_some() {
+            flags.push(String::from(write_flag(SecurityLevel::None)));
+        }
+        match self.value_changes {
+            Some(ValueChanges::Notify) if self.properties.notify.is_none() => {
+                flags.push(String::from("notify"))
+            }
+            Some(ValueChanges::Indicate) if self.properties.indicate.is_none() => {
+                flags.push(String::from("indicate"))
+            }
+            _ => {}
+        }
+        if self.reliable_write {
+            flags.push(String::from("reliable-write"));
+        }
+        if self.writable_auxiliaries {
+            flags.push(String::from("writable-auxiliaries"));
+        }
+        if self.authorize {
+            flags.push(String::from("authorize"));
+        }
+        flags
+    }
+}
+
+impl Flags for Descriptor {
+    fn flags(self: &Self) -> Vec<String> {
+        let mut flags = self.properties.flags();
+        stored_value_flags(&mut flags, self.properties.read.is_some(), &self.value);
+        if self.authorize {
+            flags.push(String::from("authorize"));
+        }
+        flags
+    }
+}
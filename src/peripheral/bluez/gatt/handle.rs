@@ -0,0 +1,18 @@
+use dbus_crossroads::IfaceBuilder;
+use std::sync::{
+    atomic::{AtomicU16, Ordering},
+    Arc,
+};
+
+/// Exposes the ATT handle of an attribute. BlueZ tries to place the attribute at a handle other
+/// than 0, and sets the handle it allocated otherwise.
+pub fn handle_property<T: Send + 'static>(b: &mut IfaceBuilder<T>, handle: Option<u16>) {
+    let handle = Arc::new(AtomicU16::new(handle.unwrap_or(0)));
+    let handle_set = Arc::clone(&handle);
+    b.property("Handle")
+        .get(move |_ctx, _data| Ok(handle.load(Ordering::Relaxed)))
+        .set(move |_ctx, _data, value| {
+            handle_set.store(value, Ordering::Relaxed);
+            Ok(Some(value))
+        });
+}
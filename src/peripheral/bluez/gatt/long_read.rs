@@ -0,0 +1,84 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+use super::super::constants::LONG_READ_TIMEOUT;
+use crate::Central;
+
+type Values = HashMap<Option<Central>, (Instant, Vec<u8>)>;
+
+/// The value each central is reading in parts. A read at offset 0 starts over, the read
+/// blob requests that follow are served from the value read then.
+#[derive(Debug, Clone, Default)]
+pub struct LongRead {
+    values: Arc<Mutex<Values>>,
+}
+
+impl LongRead {
+    /// The value being read by `central`, if `offset` continues a long read
+    pub fn get(&self, central: &Option<Central>, offset: u16) -> Option<Vec<u8>> {
+        let mut values = self.values.lock().unwrap();
+        match values.remove(central) {
+            Some((read_at, value)) if offset != 0 && read_at.elapsed() < LONG_READ_TIMEOUT => {
+                Some(value)
+            }
+            _ => None,
+        }
+    }
+
+    /// Keeps `value` for the next part of the read, unless `offset` reached its last part
+    pub fn keep(&self, central: &Option<Central>, value: &[u8], offset: u16, mtu: u16) {
+        // A read response carries up to the ATT MTU minus its opcode
+        let end = usize::from(offset) + usize::from(mtu.saturating_sub(1));
+        if end < value.len() {
+            self.values
+                .lock()
+                .unwrap()
+                .insert(central.clone(), (Instant::now(), value.to_vec()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_serves_later_parts_from_the_cache() {
+        let long_read = LongRead::default();
+        let central = Some(Central::new("AA:BB:CC:DD:EE:FF"));
+        let value = (0..40).collect::<Vec<u8>>();
+
+        long_read.keep(&central, &value, 0, 23);
+        assert_eq!(long_read.get(&central, 22), Some(value.clone()));
+
+        // The last part isn't followed by another, so nothing is kept for it
+        long_read.keep(&central, &value, 22, 23);
+        assert_eq!(long_read.get(&central, 40), None);
+    }
+
+    #[test]
+    fn it_starts_over_at_offset_0() {
+        let long_read = LongRead::default();
+        let central = Some(Central::new("AA:BB:CC:DD:EE:FF"));
+
+        long_read.keep(&central, &[1; 40], 0, 23);
+        assert_eq!(long_read.get(&central, 0), None);
+        assert_eq!(long_read.get(&central, 22), None);
+    }
+
+    #[test]
+    fn it_keeps_values_per_central() {
+        let long_read = LongRead::default();
+        let central = Some(Central::new("AA:BB:CC:DD:EE:FF"));
+        let other = Some(Central::new("00:11:22:33:44:55"));
+
+        long_read.keep(&central, &[1; 40], 0, 23);
+        long_read.keep(&other, &[2; 40], 0, 23);
+        assert_eq!(long_read.get(&None, 22), None);
+        assert_eq!(long_read.get(&other, 22), Some(vec![2; 40]));
+        assert_eq!(long_read.get(&central, 22), Some(vec![1; 40]));
+    }
+}
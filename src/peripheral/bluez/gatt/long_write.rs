@@ -0,0 +1,101 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use super::super::constants::MAX_ATTRIBUTE_LENGTH;
+use crate::{gatt::event::Response, Central};
+
+/// The value each central is writing in parts. BlueZ queues prepared writes itself and only hands
+/// them over, one at a time, once the central executes the queue.
+#[derive(Debug, Clone, Default)]
+pub struct LongWrite {
+    values: Arc<Mutex<HashMap<Option<Central>, Vec<u8>>>>,
+}
+
+impl LongWrite {
+    /// Adds a part to the value of `central`, returning the value so far. A part at offset 0
+    /// starts a new value, the previous one is dropped along with any part that doesn't
+    /// continue it.
+    pub fn push(
+        &self,
+        central: &Option<Central>,
+        offset: u16,
+        data: &[u8],
+    ) -> Result<Vec<u8>, Response> {
+        let mut values = self.values.lock().unwrap();
+        let mut value = values.remove(central).unwrap_or_default();
+        if offset == 0 {
+            value.clear();
+        }
+
+        if offset as usize != value.len() {
+            return Err(Response::InvalidOffset);
+        }
+        value.extend_from_slice(data);
+        if value.len() > MAX_ATTRIBUTE_LENGTH {
+            return Err(Response::InvalidAttributeLength);
+        }
+
+        values.insert(central.clone(), value.clone());
+        Ok(value)
+    }
+
+    /// Drops the value of `central`, e.g. when it's rejected or the central writes otherwise
+    pub fn clear(&self, central: &Option<Central>) {
+        self.values.lock().unwrap().remove(central);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_returns_the_whole_value_with_the_last_part() {
+        let long_write = LongWrite::default();
+        let central = Some(Central::new("AA:BB:CC:DD:EE:FF"));
+
+        // Parts filling an MTU of 23 exactly, nothing waits for a shorter one
+        let value = (0..36).collect::<Vec<u8>>();
+        let first = long_write.push(&central, 0, &value[..18]).ok();
+        assert_eq!(first, Some(value[..18].to_vec()));
+        let last = long_write.push(&central, 18, &value[18..]).ok();
+        assert_eq!(last, Some(value));
+
+        // Values are kept per central
+        assert_eq!(long_write.push(&None, 0, &[1]).ok(), Some(vec![1]));
+        let continued = long_write.push(&central, 36, &[36]).ok();
+        assert_eq!(continued.map(|value| value.len()), Some(37));
+    }
+
+    #[test]
+    fn it_drops_an_abandoned_value() {
+        let long_write = LongWrite::default();
+        let central = Some(Central::new("AA:BB:CC:DD:EE:FF"));
+
+        assert_eq!(long_write.push(&central, 0, &[1, 2]).ok(), Some(vec![1, 2]));
+        assert_eq!(long_write.push(&central, 0, &[3]).ok(), Some(vec![3]));
+        assert_eq!(long_write.push(&central, 1, &[4]).ok(), Some(vec![3, 4]));
+
+        // A part which doesn't continue the value drops it
+        let skipped = long_write.push(&central, 5, &[5]);
+        assert!(matches!(skipped, Err(Response::InvalidOffset)));
+        let continued = long_write.push(&central, 2, &[5]);
+        assert!(matches!(continued, Err(Response::InvalidOffset)));
+
+        assert_eq!(long_write.push(&central, 0, &[6]).ok(), Some(vec![6]));
+        long_write.clear(&central);
+        let continued = long_write.push(&central, 1, &[7]);
+        assert!(matches!(continued, Err(Response::InvalidOffset)));
+    }
+
+    #[test]
+    fn it_rejects_values_longer_than_an_attribute() {
+        let long_write = LongWrite::default();
+        let value = vec![0; MAX_ATTRIBUTE_LENGTH];
+        assert!(long_write.push(&None, 0, &value).is_ok());
+        let longer = long_write.push(&None, MAX_ATTRIBUTE_LENGTH as u16, &[0]);
+        assert!(matches!(longer, Err(Response::InvalidAttributeLength)));
+    }
+}
@@ -1,32 +1,61 @@
+mod acquired_flag;
 mod application;
 mod characteristic;
+mod database_hash;
 mod descriptor;
 mod flags;
+mod handle;
+mod long_read;
+mod long_write;
+mod notifier;
+mod request;
 mod service;
 
 use dbus::{channel::MatchingReceiver, message::MatchRule, Path};
-use std::sync::{Arc, Mutex};
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+use uuid::Uuid;
 
 use self::{
     application::Application, characteristic::Characteristic, descriptor::Descriptor,
     service::Service,
 };
-use super::{common, constants::PATH_BASE, Connection};
-use crate::{gatt, Error};
+use super::{centrals::Centrals, common, constants::PATH_BASE, Connection};
+use crate::{
+    gatt::{self, service::ServiceHandle, ResponseDeadline},
+    Error, ErrorType,
+};
 
 #[derive(Debug)]
+struct AddedService {
+    uuid: Uuid,
+    // The object paths of the service, its characteristics and their descriptors
+    object_paths: Vec<Path<'static>>,
+    includes: Vec<ServiceHandle>,
+    hash: u64,
+}
+
+#[derive(Debug, Clone)]
 pub struct Gatt {
     connection: Arc<Connection>,
+    centrals: Centrals,
     adapter: Path<'static>,
-    tree: Arc<Mutex<Option<common::Tree>>>,
+    tree: Arc<Mutex<common::Tree>>,
     application: Arc<Mutex<Option<Application>>>,
+    services: Arc<Mutex<BTreeMap<ServiceHandle, AddedService>>>,
+    // The hash of the database BlueZ last registered, while the application is registered
+    registered_hash: Arc<Mutex<Option<u64>>>,
+    reregistration: Arc<tokio::sync::Mutex<()>>,
+    response_deadline: request::DefaultDeadline,
     service_index: Arc<Mutex<u64>>,
     characteristic_index: Arc<Mutex<u64>>,
     descriptor_index: Arc<Mutex<u64>>,
 }
 
 impl Gatt {
-    pub fn new(connection: Arc<Connection>, adapter: Path<'static>) -> Self {
+    pub fn new(connection: Arc<Connection>, centrals: Centrals, adapter: Path<'static>) -> Self {
         let mut tree = common::Tree::new();
         tree.set_async_support(Some((
             connection.default.clone(),
@@ -34,58 +63,218 @@ impl Gatt {
                 tokio::spawn(x);
             }),
         )));
+        // Objects added or removed below the application once it's registered are announced
+        // with `InterfacesAdded` and `InterfacesRemoved`. BlueZ ignores them though, it only
+        // reads the objects while registering the application, see `database_changed`.
+        tree.set_object_manager_support(Some(connection.default.clone()));
+        let tree = Arc::new(Mutex::new(tree));
+
+        let mut match_rule = MatchRule::new_method_call();
+        match_rule.path = Some(PATH_BASE.into());
+        match_rule.path_is_namespace = true;
+        let tree_receive = Arc::clone(&tree);
+        connection.default.start_receive(
+            match_rule,
+            Box::new(move |msg, conn| {
+                tree_receive
+                    .lock()
+                    .unwrap()
+                    .handle_message(msg, conn)
+                    .unwrap();
+                true
+            }),
+        );
+
         Gatt {
             adapter,
             connection,
-            tree: Arc::new(Mutex::new(Some(tree))),
+            centrals,
+            tree,
             application: Arc::new(Mutex::new(None)),
+            services: Arc::new(Mutex::new(BTreeMap::new())),
+            registered_hash: Arc::new(Mutex::new(None)),
+            reregistration: Arc::new(tokio::sync::Mutex::new(())),
+            response_deadline: Arc::new(Mutex::new(None)),
             service_index: Arc::new(Mutex::new(0)),
             characteristic_index: Arc::new(Mutex::new(0)),
             descriptor_index: Arc::new(Mutex::new(0)),
         }
     }
 
-    pub fn add_service(self: &Self, service: &gatt::service::Service) -> Result<(), Error> {
+    pub async fn add_service(
+        self: &Self,
+        service: &gatt::service::Service,
+    ) -> Result<ServiceHandle, Error> {
+        let handle = self.insert_service(service)?;
+        self.database_changed().await?;
+        Ok(handle)
+    }
+
+    /// Removes a service along with its characteristics and descriptors
+    pub async fn remove_service(&self, handle: ServiceHandle) -> Result<(), Error> {
+        self.delete_service(handle)?;
+        self.database_changed().await
+    }
+
+    fn insert_service(&self, service: &gatt::service::Service) -> Result<ServiceHandle, Error> {
+        let mut services = self.services.lock().unwrap();
+        let mut problems = gatt::validation::problems(service);
+        if services.values().any(|added| added.uuid == service.uuid) {
+            problems.push(gatt::Problem::DuplicateService {
+                service: service.uuid,
+            });
+        }
+        if !problems.is_empty() {
+            return Err(Error::invalid(problems));
+        }
+
+        let includes = service
+            .includes
+            .iter()
+            .map(|include| {
+                services
+                    .get(include)
+                    .map(|added| added.object_paths[0].clone())
+                    .ok_or_else(|| {
+                        Error::new(
+                            "Unknown included service",
+                            "Included services have to be added to the same peripheral first",
+                            ErrorType::Bluez,
+                        )
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let hash = database_hash::service_hash(service, &includes);
+
         let mut tree = self.tree.lock().unwrap();
-        let tree = tree.as_mut().unwrap();
+        let tree = &mut *tree;
 
         let mut service_index = self.service_index.lock().unwrap();
         let mut characteristic_index = self.characteristic_index.lock().unwrap();
         let mut descriptor_index = self.descriptor_index.lock().unwrap();
 
-        let gatt_service = Service::new(tree, &Arc::new(service.clone()), *service_index)?;
+        let handle = ServiceHandle::new(*service_index);
+        let gatt_service =
+            Service::new(tree, &Arc::new(service.clone()), includes, *service_index)?;
         *service_index += 1;
+        let mut object_paths = vec![gatt_service.object_path.clone()];
 
         for characteristic in service.characteristics.iter() {
             let gatt_characteristic = Characteristic::new(
                 &self.connection.clone(),
+                &self.centrals,
+                &self.response_deadline,
                 tree,
                 &Arc::new(characteristic.clone()),
-                &Arc::new(gatt_service.object_path.clone()),
+                &gatt_service,
                 *characteristic_index,
             )?;
             *characteristic_index += 1;
+            object_paths.push(gatt_characteristic.object_path.clone());
 
             for descriptor in characteristic.descriptors.iter() {
-                Descriptor::new(
+                let gatt_descriptor = Descriptor::new(
+                    &self.centrals,
+                    &self.response_deadline,
                     tree,
                     &Arc::new(descriptor.clone()),
-                    &Arc::new(gatt_characteristic.object_path.clone()),
+                    &gatt_characteristic,
                     *descriptor_index,
                 )?;
                 *descriptor_index += 1;
+                object_paths.push(gatt_descriptor.object_path);
             }
         }
 
+        services.insert(
+            handle,
+            AddedService {
+                uuid: service.uuid,
+                hash,
+                object_paths,
+                includes: service.includes.clone(),
+            },
+        );
+        Ok(handle)
+    }
+
+    fn delete_service(&self, handle: ServiceHandle) -> Result<(), Error> {
+        let mut services = self.services.lock().unwrap();
+        if services
+            .values()
+            .any(|added| added.includes.contains(&handle))
+        {
+            return Err(Error::new(
+                "Service included",
+                "Services including the service have to be removed first",
+                ErrorType::Bluez,
+            ));
+        }
+        let added = services.remove(&handle).ok_or_else(|| {
+            Error::new(
+                "Unknown service",
+                "The service was never added or already removed",
+                ErrorType::Bluez,
+            )
+        })?;
+
+        let mut tree = self.tree.lock().unwrap();
+        // Descriptors first, so no object outlives its parent
+        for object_path in added.object_paths.iter().rev() {
+            tree.remove::<common::GattDataType>(object_path);
+        }
         Ok(())
     }
 
-    pub async fn register(self: &Self) -> Result<(), Error> {
-        let mut tree = self.tree.lock().unwrap().take().unwrap();
+    pub fn database_hash(&self) -> u64 {
+        database_hash::database_hash(
+            self.services
+                .lock()
+                .unwrap()
+                .values()
+                .map(|added| added.hash),
+        )
+    }
+
+    // BlueZ doesn't pick up services changed under a registered application, so it's
+    // registered again
+    async fn database_changed(&self) -> Result<(), Error> {
+        self.reregister(false).await
+    }
+
+    /// Registers the application again, so BlueZ updates its database and sends a Service
+    /// Changed indication to the centrals. Skipped when the database hash didn't change,
+    /// unless `forced`. The application is left unregistered when registering it fails.
+    pub async fn reregister(&self, forced: bool) -> Result<(), Error> {
+        let _reregistration = self.reregistration.lock().await;
+        // Checked once no other registration is in progress, which may have changed it
+        let hash = self.database_hash();
+        let registered_hash = *self.registered_hash.lock().unwrap();
+        if !needs_reregistration(registered_hash, hash, forced) {
+            return Ok(());
+        }
+
+        let application = self.application.lock().unwrap().clone();
+        if let Some(application) = application {
+            application.unregister().await?;
+            if let Err(err) = application.register().await {
+                self.registered_hash.lock().unwrap().take();
+                return Err(err);
+            }
+            self.registered_hash.lock().unwrap().replace(hash);
+        }
+        Ok(())
+    }
 
+    pub fn set_response_deadline(&self, response_deadline: Option<ResponseDeadline>) {
+        *self.response_deadline.lock().unwrap() = response_deadline;
+    }
+
+    pub async fn register(self: &Self) -> Result<(), Error> {
+        let _reregistration = self.reregistration.lock().await;
         let new_application = Application::new(
             Arc::clone(&self.connection),
-            &mut tree,
+            &mut self.tree.lock().unwrap(),
             self.adapter.clone(),
         );
 
@@ -94,28 +283,38 @@ impl Gatt {
             .unwrap()
             .replace(new_application.clone());
 
-        let mut match_rule = MatchRule::new_method_call();
-        match_rule.path = Some(PATH_BASE.into());
-        match_rule.path_is_namespace = true;
-        self.connection.default.start_receive(
-            match_rule,
-            Box::new(move |msg, conn| {
-                tree.handle_message(msg, conn).unwrap();
-                true
-            }),
-        );
-
-        new_application.register().await
+        let hash = self.database_hash();
+        new_application.register().await?;
+        self.registered_hash.lock().unwrap().replace(hash);
+        Ok(())
     }
 
     pub async fn unregister(self: &Self) -> Result<(), Error> {
-        self.application
-            .lock()
-            .unwrap()
-            .take()
-            .unwrap()
-            .unregister()
-            .await
-            .map(|_| ())
+        let _reregistration = self.reregistration.lock().await;
+        self.registered_hash.lock().unwrap().take();
+        let application = self.application.lock().unwrap().take().unwrap();
+        application.unregister().await.map(|_| ())
+    }
+}
+
+// Whether the registered application is out of date, it isn't when it's not registered at all
+fn needs_reregistration(registered_hash: Option<u64>, hash: u64, forced: bool) -> bool {
+    match registered_hash {
+        None => false,
+        Some(registered_hash) => registered_hash != hash || forced,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_reregisters_changed_databases_only() {
+        assert!(!needs_reregistration(None, 1, false));
+        assert!(!needs_reregistration(None, 1, true));
+        assert!(!needs_reregistration(Some(1), 1, false));
+        assert!(needs_reregistration(Some(1), 1, true));
+        assert!(needs_reregistration(Some(1), 2, false));
     }
 }
@@ -0,0 +1,160 @@
+use dbus::{channel::Sender, Path};
+use futures::{channel::oneshot, future, prelude::*};
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+use super::super::{
+    acquired_socket::AcquiredSocket, common, constants::GATT_CHARACTERISTIC_IFACE, Connection,
+};
+use crate::gatt::{
+    event::{ConfirmationSender, Indication, NotificationError, Subscription},
+    notification_queue, NotificationQueue,
+};
+
+/// How BlueZ subscribed to a characteristic, a subscription acquired through a socket ends when
+/// the socket is closed while the others end with `StopNotify`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Origin {
+    StartNotify,
+    AcquireNotify,
+}
+
+/// Closes a subscription when dropped
+type Closer = (Origin, oneshot::Sender<()>);
+
+/// Delivers the notifications of a characteristic, through the socket handed out by
+/// `AcquireNotify` while BlueZ holds it and as `PropertiesChanged` signals otherwise
+#[derive(Debug, Clone)]
+pub struct Notifier {
+    connection: Arc<Connection>,
+    object_path: Path<'static>,
+    socket: Arc<Mutex<Option<Arc<AcquiredSocket>>>>,
+    confirmations: Arc<Mutex<VecDeque<(Origin, ConfirmationSender)>>>,
+    subscriptions: Arc<Mutex<Vec<Closer>>>,
+}
+
+impl Notifier {
+    pub fn new(connection: &Arc<Connection>, object_path: &Path<'static>) -> Self {
+        Notifier {
+            connection: Arc::clone(connection),
+            object_path: object_path.clone(),
+            socket: Arc::new(Mutex::new(None)),
+            confirmations: Arc::new(Mutex::new(VecDeque::new())),
+            subscriptions: Arc::new(Mutex::new(vec![])),
+        }
+    }
+
+    /// Sends notifications through `socket` until its other end is closed, which resolves the
+    /// returned future
+    pub async fn acquire(&self, socket: AcquiredSocket) {
+        let socket = Arc::new(socket);
+        self.socket.lock().unwrap().replace(Arc::clone(&socket));
+        socket.closed().await;
+        let mut current = self.socket.lock().unwrap();
+        if matches!(current.as_ref(), Some(current) if Arc::ptr_eq(current, &socket)) {
+            current.take();
+        }
+    }
+
+    /// Hands out a subscription relaying every value to `relay`, until `unsubscribe` is called
+    /// for its `origin`
+    pub fn subscribe<T, F, Fut>(
+        &self,
+        origin: Origin,
+        queue: NotificationQueue,
+        mut relay: F,
+    ) -> Subscription<T>
+    where
+        T: Send + 'static,
+        F: FnMut(Notifier, T) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<(), NotificationError>> + Send + 'static,
+    {
+        let (closer, closed) = oneshot::channel();
+        self.subscriptions.lock().unwrap().push((origin, closer));
+        let (subscription, receiver) = Subscription::new(queue, closed);
+
+        let notifier = self.clone();
+        let closed = subscription.closed();
+        tokio::spawn(async move {
+            let relaying = async {
+                while let Some(delivery) = receiver.recv().await {
+                    let (value, report) = delivery.into_parts();
+                    notification_queue::report(report, relay(notifier.clone(), value).await);
+                }
+            };
+            futures::pin_mut!(relaying, closed);
+            future::select(relaying, closed).await;
+        });
+        subscription
+    }
+
+    /// Closes the subscriptions made through `origin`, failing their indications still waiting
+    /// for a confirmation
+    pub fn unsubscribe(&self, origin: Origin) {
+        self.subscriptions
+            .lock()
+            .unwrap()
+            .retain(|(subscribed, _)| *subscribed != origin);
+        self.confirmations
+            .lock()
+            .unwrap()
+            .retain(|(subscribed, _)| *subscribed != origin);
+    }
+
+    pub async fn notify(&self, notification: Vec<u8>) -> Result<(), NotificationError> {
+        let socket = self.socket.lock().unwrap().clone();
+        if let Some(socket) = socket {
+            match socket.send(&notification).await {
+                Ok(()) => return Ok(()),
+                Err(err) => log::warn!("Failed to notify through acquired socket: {:?}", err),
+            }
+        }
+
+        // For notifications, BlueZ wants a PropertiesChanged signal on the optional `Value`
+        // property. It doesn't require that the property actually exists.
+        let signal_message = common::properties_changed(
+            &self.object_path,
+            GATT_CHARACTERISTIC_IFACE,
+            "Value",
+            Box::new(notification),
+        );
+        self.connection
+            .default
+            .send(signal_message)
+            .map(|_| ())
+            .map_err(|_| NotificationError::Failed(String::from("D-Bus signal not sent")))
+    }
+
+    /// BlueZ sends an indication when the central enabled them, the same way as a notification
+    pub async fn indicate(
+        &self,
+        origin: Origin,
+        indication: Indication,
+    ) -> Result<(), NotificationError> {
+        self.confirmations
+            .lock()
+            .unwrap()
+            .push_back((origin, indication.confirmation));
+        let result = self.notify(indication.value).await;
+        if result.is_err() {
+            self.confirmations.lock().unwrap().pop_back();
+        }
+        result
+    }
+
+    /// Called by BlueZ when a central confirms an indication, they're confirmed in order. It's
+    /// called for every central the indication reached, without telling which, so this assumes
+    /// a single central is subscribed: with more, an indication can be taken as confirmed by the
+    /// confirmation another central sent for an earlier one.
+    pub fn confirm(&self) {
+        let mut confirmations = self.confirmations.lock().unwrap();
+        while let Some((_, confirmation)) = confirmations.pop_front() {
+            // Skip indications which already timed out
+            if confirmation.send(()).is_ok() {
+                break;
+            }
+        }
+    }
+}
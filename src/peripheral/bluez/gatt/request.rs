@@ -0,0 +1,332 @@
+use dbus_crossroads::MethodErr;
+use futures::{channel::oneshot, prelude::*};
+use std::sync::{Arc, Mutex};
+use tokio::sync::watch;
+
+use super::{
+    super::{
+        centrals::Centrals,
+        constants::{
+            BLUEZ_ERROR_FAILED, BLUEZ_ERROR_INPROGRESS, BLUEZ_ERROR_INVALIDOFFSET,
+            BLUEZ_ERROR_INVALIDVALUELENGTH, BLUEZ_ERROR_NOTAUTHORIZED, BLUEZ_ERROR_NOTPERMITTED,
+            BLUEZ_ERROR_NOTSUPPORTED, BLUEZ_ERROR_UNLIKELY, MAX_ATTRIBUTE_LENGTH,
+        },
+    },
+    long_read::LongRead,
+    long_write::LongWrite,
+};
+use crate::{
+    gatt::{
+        event::{
+            Authorization, AuthorizeRequest, Event, EventSender, Operation, ReadRequest, Response,
+            WriteRequest,
+        },
+        Attribute, ResponseDeadline,
+    },
+    Central, PeripheralEvent, ResponseTimedOut,
+};
+
+/// The deadline set on the peripheral, used by attributes without one of their own
+pub type DefaultDeadline = Arc<Mutex<Option<ResponseDeadline>>>;
+
+/// The attribute and central a request is made for, and how long its handler has to answer
+#[derive(Debug, Clone)]
+pub struct Target {
+    pub attribute: Attribute,
+    pub central: Option<Central>,
+    deadline: Option<ResponseDeadline>,
+    centrals: Centrals,
+}
+
+impl Target {
+    pub fn new(
+        attribute: &Attribute,
+        central: Option<Central>,
+        deadline: &Option<ResponseDeadline>,
+        default_deadline: &DefaultDeadline,
+        centrals: &Centrals,
+    ) -> Self {
+        Target {
+            attribute: attribute.clone(),
+            central,
+            deadline: deadline
+                .clone()
+                .or_else(|| default_deadline.lock().unwrap().clone()),
+            centrals: centrals.clone(),
+        }
+    }
+}
+
+/// Asks the handler whether the central may go ahead, a dropped response or one past the
+/// deadline of the target counts as a denial
+pub async fn authorize(
+    mut event_sender: EventSender,
+    target: &Target,
+    operation: Operation,
+    offset: u16,
+    mtu: u16,
+) -> Result<(), MethodErr> {
+    let (sender, receiver) = oneshot::channel();
+    event_sender
+        .send(Event::AuthorizeRequest(AuthorizeRequest {
+            attribute: target.attribute.clone(),
+            central: target.central.clone(),
+            operation,
+            offset,
+            response: sender,
+            mtu,
+        }))
+        .await
+        .map_err(|_| MethodErr::from((BLUEZ_ERROR_FAILED, "")))?;
+    let authorization = match &target.deadline {
+        None => receiver.await.ok(),
+        Some(deadline) => match tokio::time::timeout(deadline.timeout, receiver).await {
+            Ok(authorization) => authorization.ok(),
+            Err(_) => {
+                report_timeout(target, operation);
+                None
+            }
+        },
+    };
+    match authorization {
+        Some(Authorization::Granted) => Ok(()),
+        _ => Err(MethodErr::from((BLUEZ_ERROR_NOTAUTHORIZED, ""))),
+    }
+}
+
+/// Answers a read from a value bluster keeps, starting at `offset`
+pub fn read_value(value: &[u8], offset: u16) -> Result<Vec<u8>, MethodErr> {
+    value
+        .get(usize::from(offset)..)
+        .map(<[u8]>::to_vec)
+        .ok_or_else(|| response_error(Response::InvalidOffset))
+}
+
+/// Sends a write on the channel of `sender`, keeping the current value up to `offset`
+pub fn write_value(
+    sender: &watch::Sender<Vec<u8>>,
+    data: &[u8],
+    offset: u16,
+) -> Result<(), MethodErr> {
+    let mut value = sender
+        .borrow()
+        .get(..usize::from(offset))
+        .map(<[u8]>::to_vec)
+        .ok_or_else(|| response_error(Response::InvalidOffset))?;
+    value.extend_from_slice(data);
+    if value.len() > MAX_ATTRIBUTE_LENGTH {
+        return Err(response_error(Response::InvalidAttributeLength));
+    }
+    sender.send_replace(value);
+    Ok(())
+}
+
+pub async fn read(
+    mut event_sender: EventSender,
+    target: &Target,
+    offset: u16,
+    mtu: u16,
+) -> Result<Vec<u8>, MethodErr> {
+    let (sender, receiver) = oneshot::channel();
+    event_sender
+        .send(Event::ReadRequest(ReadRequest {
+            attribute: target.attribute.clone(),
+            offset,
+            response: sender,
+            mtu,
+        }))
+        .await
+        .map_err(|_| MethodErr::from((BLUEZ_ERROR_FAILED, "")))?;
+    receive_response(receiver, target, Operation::Read).await
+}
+
+/// Reads the whole value from the handler at the start of a long read, serving the read blob
+/// requests that follow from it, so every part comes from the same value
+pub async fn read_cached(
+    long_read: &LongRead,
+    event_sender: EventSender,
+    target: &Target,
+    offset: u16,
+    mtu: u16,
+) -> Result<Vec<u8>, MethodErr> {
+    let value = match long_read.get(&target.central, offset) {
+        Some(value) => value,
+        None => read(event_sender, target, 0, mtu).await?,
+    };
+    let part = read_value(&value, offset)?;
+    long_read.keep(&target.central, &value, offset, mtu);
+    Ok(part)
+}
+
+pub async fn write(
+    mut event_sender: EventSender,
+    target: &Target,
+    data: Vec<u8>,
+    offset: u16,
+    without_response: bool,
+    mtu: u16,
+) -> Result<Vec<u8>, MethodErr> {
+    let (sender, receiver) = oneshot::channel();
+    event_sender
+        .send(Event::WriteRequest(WriteRequest {
+            attribute: target.attribute.clone(),
+            data,
+            offset,
+            without_response,
+            response: sender,
+            mtu,
+        }))
+        .await
+        .map_err(|_| MethodErr::from((BLUEZ_ERROR_FAILED, "")))?;
+    // BlueZ doesn't forward the reply of a write command to the central
+    if without_response {
+        return Ok(vec![]);
+    }
+    receive_response(receiver, target, Operation::Write).await
+}
+
+/// Waits for the handler to answer. Past the deadline of the target, or once the handler drops
+/// the response, the central gets the response of the deadline instead.
+async fn receive_response(
+    receiver: oneshot::Receiver<Response>,
+    target: &Target,
+    operation: Operation,
+) -> Result<Vec<u8>, MethodErr> {
+    let response = match &target.deadline {
+        None => receiver
+            .await
+            .map_err(|_| MethodErr::from((BLUEZ_ERROR_FAILED, "")))?,
+        Some(deadline) => match tokio::time::timeout(deadline.timeout, receiver).await {
+            Ok(Ok(response)) => response,
+            _ => {
+                report_timeout(target, operation);
+                deadline.response.clone()
+            }
+        },
+    };
+    match response {
+        Response::Success(value) => Ok(value),
+        response => Err(response_error(response)),
+    }
+}
+
+fn report_timeout(target: &Target, operation: Operation) {
+    log::warn!(
+        "No response to {:?} request for {} before the deadline",
+        operation,
+        target.attribute
+    );
+    target
+        .centrals
+        .emit(PeripheralEvent::ResponseTimedOut(ResponseTimedOut {
+            attribute: target.attribute.clone(),
+            operation,
+            central: target.central.clone(),
+        }));
+}
+
+/// BlueZ picks the ATT error code from the name of the error. It takes the code of a failure
+/// from its message only for application errors, any other failure is sent as 0x80.
+fn response_error(response: Response) -> MethodErr {
+    let failure = |code: u8| MethodErr::from((BLUEZ_ERROR_FAILED, format!("0x{:02X}", code)));
+    let unlikely = || MethodErr::from((BLUEZ_ERROR_UNLIKELY, ""));
+    match response {
+        Response::Success(_) | Response::UnlikelyError => unlikely(),
+        Response::InvalidOffset => MethodErr::from((BLUEZ_ERROR_INVALIDOFFSET, "")),
+        Response::InvalidAttributeLength => MethodErr::from((BLUEZ_ERROR_INVALIDVALUELENGTH, "")),
+        Response::NotPermitted => MethodErr::from((BLUEZ_ERROR_NOTPERMITTED, "")),
+        Response::NotAuthorized => MethodErr::from((BLUEZ_ERROR_NOTAUTHORIZED, "")),
+        Response::NotSupported => MethodErr::from((BLUEZ_ERROR_NOTSUPPORTED, "")),
+        Response::ApplicationError(code @ 0x80..=0x9F) => failure(code),
+        Response::ApplicationError(code) => {
+            log::warn!(
+                "0x{:02X} isn't an application error code, sending 0x0E",
+                code
+            );
+            unlikely()
+        }
+        Response::WriteRequestRejected => failure(0xFC),
+        Response::CccdImproperlyConfigured => failure(0xFD),
+        Response::ProcedureAlreadyInProgress => MethodErr::from((BLUEZ_ERROR_INPROGRESS, "")),
+        Response::OutOfRange => failure(0xFF),
+    }
+}
+
+/// Adds a part of a long or reliable write to the value of the central, and writes the value so
+/// far to the handler at offset 0. Its response is the response to the part, which BlueZ passes
+/// on to the central as the response to the execute request, so the central learns when a
+/// value is rejected and the handler always holds the latest value.
+pub async fn assemble(
+    long_write: &LongWrite,
+    event_sender: EventSender,
+    target: &Target,
+    data: &[u8],
+    offset: u16,
+    mtu: u16,
+) -> Result<(), MethodErr> {
+    let value = long_write
+        .push(&target.central, offset, data)
+        .map_err(response_error)?;
+    let written = write(event_sender, target, value, 0, false, mtu).await;
+    if written.is_err() {
+        long_write.clear(&target.central);
+    }
+    written.map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(response: Response) -> (String, String) {
+        let err = response_error(response);
+        (err.errorname().to_string(), err.description().to_owned())
+    }
+
+    #[test]
+    fn it_maps_responses_to_errors_bluez_understands() {
+        let named = |name: &str| (name.to_owned(), String::new());
+        let failure = |code: &str| (BLUEZ_ERROR_FAILED.to_owned(), code.to_owned());
+
+        assert_eq!(
+            error(Response::InvalidOffset),
+            named(BLUEZ_ERROR_INVALIDOFFSET)
+        );
+        assert_eq!(
+            error(Response::InvalidAttributeLength),
+            named(BLUEZ_ERROR_INVALIDVALUELENGTH)
+        );
+        assert_eq!(
+            error(Response::NotPermitted),
+            named(BLUEZ_ERROR_NOTPERMITTED)
+        );
+        assert_eq!(
+            error(Response::NotAuthorized),
+            named(BLUEZ_ERROR_NOTAUTHORIZED)
+        );
+        assert_eq!(
+            error(Response::NotSupported),
+            named(BLUEZ_ERROR_NOTSUPPORTED)
+        );
+        assert_eq!(
+            error(Response::ProcedureAlreadyInProgress),
+            named(BLUEZ_ERROR_INPROGRESS)
+        );
+
+        // Only failures with an application error code keep it
+        assert_eq!(error(Response::ApplicationError(0x80)), failure("0x80"));
+        assert_eq!(error(Response::ApplicationError(0x9F)), failure("0x9F"));
+        assert_eq!(error(Response::WriteRequestRejected), failure("0xFC"));
+
+        // Names BlueZ doesn't map become Unlikely Error
+        assert_eq!(error(Response::UnlikelyError), named(BLUEZ_ERROR_UNLIKELY));
+        assert_eq!(
+            error(Response::ApplicationError(0x01)),
+            named(BLUEZ_ERROR_UNLIKELY)
+        );
+        assert_eq!(
+            error(Response::Success(vec![])),
+            named(BLUEZ_ERROR_UNLIKELY)
+        );
+    }
+}
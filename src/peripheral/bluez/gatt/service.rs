@@ -1,31 +1,41 @@
 use dbus::Path;
 use std::sync::Arc;
+use uuid::Uuid;
 
 use super::super::common;
 use super::super::constants::{GATT_SERVICE_IFACE, PATH_BASE};
+use super::handle::handle_property;
 use crate::{gatt, Error};
 
 #[derive(Debug, Clone)]
 pub struct Service {
     pub object_path: Path<'static>,
+    pub uuid: Uuid,
 }
 
 impl Service {
     pub fn new(
         tree: &mut common::Tree,
         service: &Arc<gatt::service::Service>,
+        includes: Vec<Path<'static>>,
         index: u64,
     ) -> Result<Self, Error> {
         let get_all = tree.register(GATT_SERVICE_IFACE, |b| {
             let service1 = service.clone();
+            handle_property(b, service.handle);
             b.property("UUID")
                 .get(move |_ctx, _cr| Ok(service1.uuid.to_string()));
             let service1 = service.clone();
             b.property("Primary")
                 .get(move |_ctx, _cr| Ok(service1.primary));
+            b.property("Includes")
+                .get(move |_ctx, _cr| Ok(includes.clone()));
         });
         let object_path: Path = format!("{}/service{:04}", PATH_BASE, index).into();
         tree.insert(object_path.clone(), &[get_all], ());
-        Ok(Service { object_path })
+        Ok(Service {
+            object_path,
+            uuid: service.uuid,
+        })
     }
 }
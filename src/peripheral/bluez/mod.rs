@@ -1,5 +1,8 @@
+mod acquired_socket;
 mod adapter;
 mod advertisement;
+mod agent;
+mod centrals;
 mod common;
 mod connection;
 mod constants;
@@ -9,12 +12,28 @@ mod gatt;
 use std::{string::ToString, sync::Arc};
 use uuid::Uuid;
 
-use self::{adapter::Adapter, advertisement::Advertisement, connection::Connection, gatt::Gatt};
-use crate::{gatt::service::Service, Error};
+pub use self::{acquired_socket::AcquiredSocket, agent::Agent};
+
+use self::{
+    adapter::Adapter,
+    advertisement::Advertisement,
+    centrals::{path_from_central, Centrals},
+    connection::Connection,
+    gatt::Gatt,
+};
+use crate::{
+    gatt::{
+        service::{Service, ServiceHandle},
+        ResponseDeadline,
+    },
+    AgentHandler, Bond, Central, Error, IoCapability, PeripheralEventSender,
+};
 
 #[derive(Debug)]
 pub struct Peripheral {
+    connection: Arc<Connection>,
     adapter: Adapter,
+    centrals: Centrals,
     gatt: Gatt,
     advertisement: Advertisement,
 }
@@ -25,11 +44,18 @@ impl Peripheral {
         let connection = Arc::new(Connection::new()?);
         let adapter = Adapter::new(connection.clone()).await?;
         adapter.powered(true).await?;
-        let gatt = Gatt::new(connection.clone(), adapter.object_path.clone());
-        let advertisement = Advertisement::new(connection, adapter.object_path.clone());
+        let centrals = Centrals::new(&connection, &adapter.object_path).await?;
+        let gatt = Gatt::new(
+            connection.clone(),
+            centrals.clone(),
+            adapter.object_path.clone(),
+        );
+        let advertisement = Advertisement::new(connection.clone(), adapter.object_path.clone());
 
         Ok(Peripheral {
+            connection,
             adapter,
+            centrals,
             gatt,
             advertisement,
         })
@@ -47,6 +73,56 @@ impl Peripheral {
         self.adapter.is_powered().await
     }
 
+    pub fn set_event_sender(&self, event_sender: PeripheralEventSender) {
+        self.centrals.set_event_sender(event_sender);
+    }
+
+    /// How long handlers of read and write requests have to answer, unless the characteristic or
+    /// descriptor sets its own deadline
+    pub fn set_response_deadline(&self, response_deadline: Option<ResponseDeadline>) {
+        self.gatt.set_response_deadline(response_deadline);
+    }
+
+    /// The ATT MTU last reported for `central`, if it has made a request since connecting
+    pub fn mtu(&self, central: &Central) -> Option<u16> {
+        self.centrals.mtu(central)
+    }
+
+    /// Registers `handler` as the default pairing agent, which is needed for bonding with
+    /// centrals before they may access secure characteristics
+    pub async fn register_agent<H: AgentHandler>(
+        &self,
+        io_capability: IoCapability,
+        handler: H,
+    ) -> Result<Agent, Error> {
+        Agent::register_with(Arc::clone(&self.connection), io_capability, handler).await
+    }
+
+    /// The centrals which are paired or bonded with the adapter
+    pub async fn get_bonds(&self) -> Result<Vec<Bond>, Error> {
+        self.adapter.get_bonds().await
+    }
+
+    /// Removes the bond and every other record BlueZ keeps of `central`, disconnecting it
+    pub async fn remove_bond(&self, central: &Central) -> Result<(), Error> {
+        let device = path_from_central(&self.adapter.object_path, central);
+        self.adapter.remove_device(&device).await
+    }
+
+    /// Removes every bond, e.g. for a factory reset
+    pub async fn remove_all_bonds(&self) -> Result<(), Error> {
+        for bond in self.get_bonds().await? {
+            self.remove_bond(&bond.central).await?;
+        }
+        Ok(())
+    }
+
+    /// Trusted centrals may reconnect and use services without further authorization
+    pub async fn set_trusted(&self, central: &Central, trusted: bool) -> Result<(), Error> {
+        let device = path_from_central(&self.adapter.object_path, central);
+        self.adapter.set_trusted(&device, trusted).await
+    }
+
     pub async fn register_gatt(&self) -> Result<(), Error> {
         self.gatt.register().await
     }
@@ -76,7 +152,29 @@ impl Peripheral {
         Ok(self.advertisement.is_advertising())
     }
 
-    pub fn add_service(self: &Self, service: &Service) -> Result<(), Error> {
-        self.gatt.add_service(service)
+    /// Services can be added before and after the GATT application is registered. Every
+    /// problem found validating the service is listed by the error.
+    pub async fn add_service(self: &Self, service: &Service) -> Result<ServiceHandle, Error> {
+        self.gatt.add_service(service).await
+    }
+
+    /// Services added or removed while the GATT application is registered make it register
+    /// again, which is when BlueZ picks up the change and indicates Service Changed to the
+    /// centrals. When that fails, the change is kept but the application is left
+    /// unregistered, until `register_gatt` is called again.
+    pub async fn remove_service(&self, handle: ServiceHandle) -> Result<(), Error> {
+        self.gatt.remove_service(handle).await
+    }
+
+    /// A hash of the UUIDs, flags and handles of every attribute added, to tell whether the
+    /// database changed since a previous run
+    pub fn database_hash(&self) -> Option<u64> {
+        Some(self.gatt.database_hash())
+    }
+
+    /// Makes BlueZ indicate Service Changed to the centrals, even though the database hash
+    /// didn't change, by registering the GATT application again
+    pub async fn service_changed(&self) -> Result<(), Error> {
+        self.gatt.reregister(true).await
     }
 }
@@ -1,5 +1,31 @@
 use super::ffi::{CBAttributePermissions, CBCharacteristicProperties};
-use crate::gatt::characteristic::{Characteristic, Secure, Write};
+use crate::gatt::{
+    characteristic::{Characteristic, Write},
+    SecurityLevel,
+};
+
+// CoreBluetooth can only require encryption, the pairing method is up to the system
+fn read_permission(security_level: SecurityLevel) -> u8 {
+    match security_level {
+        SecurityLevel::None => CBAttributePermissions::CBAttributePermissionsReadable as u8,
+        SecurityLevel::Encrypted
+        | SecurityLevel::Authenticated
+        | SecurityLevel::SecureConnections => {
+            CBAttributePermissions::CBAttributePermissionsReadEncryptionRequired as u8
+        }
+    }
+}
+
+fn write_permission(security_level: SecurityLevel) -> u8 {
+    match security_level {
+        SecurityLevel::None => CBAttributePermissions::CBAttributePermissionsWriteable as u8,
+        SecurityLevel::Encrypted
+        | SecurityLevel::Authenticated
+        | SecurityLevel::SecureConnections => {
+            CBAttributePermissions::CBAttributePermissionsWriteEncryptionRequired as u8
+        }
+    }
+}
 
 pub fn get_properties_and_permissions(characteristic: &Characteristic) -> (u16, u8) {
     let mut properties: u16 = 0;
@@ -7,38 +33,25 @@ pub fn get_properties_and_permissions(characteristic: &Characteristic) -> (u16,
 
     if let Some(secure) = &characteristic.properties.read {
         properties |= CBCharacteristicProperties::CBCharacteristicPropertyRead as u16;
-
-        match secure.0 {
-            Secure::Secure(_) => {
-                permissions |=
-                    CBAttributePermissions::CBAttributePermissionsReadEncryptionRequired as u8;
-            }
-            Secure::Insecure(_) => {
-                permissions |= CBAttributePermissions::CBAttributePermissionsReadable as u8;
-            }
-        };
+        permissions |= read_permission(secure.security_level());
     }
 
     if let Some(write) = &characteristic.properties.write {
         match write {
             Write::WithResponse(secure) => {
                 properties |= CBCharacteristicProperties::CBCharacteristicPropertyWrite as u16;
-                match secure {
-                    Secure::Secure(_) => {
-                        permissions |=
-                            CBAttributePermissions::CBAttributePermissionsWriteEncryptionRequired
-                                as u8;
-                    }
-                    Secure::Insecure(_) => {
-                        permissions |=
-                            CBAttributePermissions::CBAttributePermissionsWriteable as u8;
-                    }
-                };
+                permissions |= write_permission(secure.security_level());
             }
             Write::WithoutResponse(_) => {
                 properties |=
                     CBCharacteristicProperties::CBCharacteristicPropertyWriteWithoutResponse as u16;
             }
+            Write::WithAndWithoutResponse(secure) => {
+                properties |= CBCharacteristicProperties::CBCharacteristicPropertyWrite as u16;
+                properties |=
+                    CBCharacteristicProperties::CBCharacteristicPropertyWriteWithoutResponse as u16;
+                permissions |= write_permission(secure.security_level());
+            }
         };
     }
 
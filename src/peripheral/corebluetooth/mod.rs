@@ -7,13 +7,21 @@ mod into_bool;
 mod into_cbuuid;
 mod peripheral_manager;
 
+use std::sync::atomic::{AtomicU64, Ordering};
 use uuid::Uuid;
 
 use self::peripheral_manager::PeripheralManager;
-use crate::{gatt::service::Service, Error};
+use crate::{
+    gatt::{
+        service::{Service, ServiceHandle},
+        validate, ResponseDeadline,
+    },
+    Bond, Central, Error, ErrorType, PeripheralEventSender,
+};
 
 pub struct Peripheral {
     peripheral_manager: PeripheralManager,
+    service_index: AtomicU64,
 }
 
 impl Peripheral {
@@ -21,6 +29,7 @@ impl Peripheral {
     pub async fn new() -> Result<Self, Error> {
         Ok(Peripheral {
             peripheral_manager: PeripheralManager::new(),
+            service_index: AtomicU64::new(0),
         })
     }
 
@@ -28,6 +37,33 @@ impl Peripheral {
         Ok(self.peripheral_manager.is_powered())
     }
 
+    // TODO: Emit `PeripheralEvent`s once the delegate forwards its callbacks
+    pub fn set_event_sender(&self, _event_sender: PeripheralEventSender) {}
+
+    // TODO: Apply the deadline once requests are answered through the delegate
+    pub fn set_response_deadline(&self, _response_deadline: Option<ResponseDeadline>) {}
+
+    pub fn mtu(&self, _central: &Central) -> Option<u16> {
+        None
+    }
+
+    // Bonds are kept by the system and aren't exposed by CoreBluetooth
+    pub async fn get_bonds(&self) -> Result<Vec<Bond>, Error> {
+        Err(bonds_not_supported())
+    }
+
+    pub async fn remove_bond(&self, _central: &Central) -> Result<(), Error> {
+        Err(bonds_not_supported())
+    }
+
+    pub async fn remove_all_bonds(&self) -> Result<(), Error> {
+        Err(bonds_not_supported())
+    }
+
+    pub async fn set_trusted(&self, _central: &Central, _trusted: bool) -> Result<(), Error> {
+        Err(bonds_not_supported())
+    }
+
     pub async fn register_gatt(&self) -> Result<(), Error> {
         Ok(())
     }
@@ -50,8 +86,66 @@ impl Peripheral {
         Ok(self.peripheral_manager.is_advertising())
     }
 
-    pub fn add_service(&self, service: &Service) -> Result<(), Error> {
+    pub async fn add_service(&self, service: &Service) -> Result<ServiceHandle, Error> {
+        validate(service)?;
+        // TODO: Set `includedServices` once added services are kept around
+        if !service.includes.is_empty() {
+            return Err(Error::new(
+                "Not supported",
+                "Included services aren't supported yet",
+                ErrorType::CoreBluetooth,
+            ));
+        }
+        // TODO: Serve, write and notify through the watch channels once CoreBluetooth
+        // requests are answered from them
+        let watched = service.characteristics.iter().any(|characteristic| {
+            characteristic.value.changes().is_some()
+                || characteristic.value_writes.is_some()
+                || characteristic.value_changes.is_some()
+        });
+        if watched {
+            return Err(Error::new(
+                "Not supported",
+                "Characteristics bound to watch channels aren't supported yet",
+                ErrorType::CoreBluetooth,
+            ));
+        }
         self.peripheral_manager.add_service(service);
-        Ok(())
+        Ok(ServiceHandle::new(
+            self.service_index.fetch_add(1, Ordering::Relaxed),
+        ))
+    }
+
+    // CoreBluetooth assigns the attribute handles and doesn't expose them, so there's no hash
+    // to compare. It keeps track of changes to the database itself.
+    pub fn database_hash(&self) -> Option<u64> {
+        None
+    }
+
+    // CoreBluetooth indicates Service Changed itself whenever services are added or removed,
+    // and can't be made to otherwise
+    pub async fn service_changed(&self) -> Result<(), Error> {
+        Err(Error::new(
+            "Not supported",
+            "Service Changed is indicated by the system",
+            ErrorType::CoreBluetooth,
+        ))
+    }
+
+    // TODO: Keep the added `CBMutableService`s around to remove them
+    pub async fn remove_service(&self, _handle: ServiceHandle) -> Result<(), Error> {
+        Err(Error::new(
+            "Not supported",
+            "Services can't be removed yet",
+            ErrorType::CoreBluetooth,
+        ))
     }
 }
+
+fn bonds_not_supported() -> Error {
+    Error::new(
+        "Not supported",
+        "Bonds are managed by the system",
+        ErrorType::CoreBluetooth,
+    )
+}
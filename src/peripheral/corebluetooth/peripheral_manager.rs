@@ -167,7 +167,7 @@ impl PeripheralManager {
                     let obj: *mut Object = msg_send![cls, alloc];
 
                     let init_with_type = characteristic.uuid.into_cbuuid();
-                    let mutable_characteristic: *mut Object = match characteristic.value {
+                    let mutable_characteristic: *mut Object = match characteristic.value.get() {
                         Some(ref value) => msg_send![obj, initWithType:init_with_type
                                                             properties:properties
                                                                  value:NSData::with_bytes(value)
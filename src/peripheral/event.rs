@@ -0,0 +1,30 @@
+use futures::channel::mpsc;
+
+use crate::{
+    gatt::{event::Operation, Attribute},
+    Central,
+};
+
+pub type PeripheralEventSender = mpsc::Sender<PeripheralEvent>;
+
+/// Connection-level events which don't belong to a single GATT attribute
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum PeripheralEvent {
+    MtuChanged(MtuChanged),
+    ResponseTimedOut(ResponseTimedOut),
+}
+
+#[derive(Debug, Clone)]
+pub struct MtuChanged {
+    pub central: Central,
+    pub mtu: u16,
+}
+
+/// A handler didn't answer a request before its `ResponseDeadline`
+#[derive(Debug, Clone)]
+pub struct ResponseTimedOut {
+    pub attribute: Attribute,
+    pub operation: Operation,
+    pub central: Option<Central>,
+}
@@ -1,3 +1,6 @@
+mod event;
+pub use self::event::*;
+
 #[cfg(any(target_os = "macos", target_os = "ios"))]
 mod corebluetooth;
 #[cfg(any(target_os = "macos", target_os = "ios"))]
@@ -6,7 +9,7 @@ pub use self::corebluetooth::Peripheral;
 #[cfg(any(target_os = "linux", target_os = "android"))]
 mod bluez;
 #[cfg(any(target_os = "linux", target_os = "android"))]
-pub use self::bluez::Peripheral;
+pub use self::bluez::{AcquiredSocket, Agent, Peripheral};
 
 // TODO: Add struct / traits to implement for each OS
 //
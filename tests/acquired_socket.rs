@@ -0,0 +1,67 @@
+#![cfg(any(target_os = "linux", target_os = "android"))]
+
+use futures::prelude::*;
+use std::{
+    os::unix::net::UnixDatagram,
+    sync::{Arc, Mutex},
+};
+
+use bluster::AcquiredSocket;
+
+#[tokio::test]
+async fn it_streams_payloads_until_closed() {
+    let (socket, remote) = AcquiredSocket::pair(23).unwrap();
+    assert_eq!(socket.mtu(), 23);
+
+    // Stands in for BlueZ, which writes one packet per write without response
+    let remote = UnixDatagram::from(remote);
+    remote.send(&[1, 2, 3]).unwrap();
+    remote.send(&[4]).unwrap();
+    remote.send(&[0xFF; 20]).unwrap();
+    drop(remote);
+
+    let payloads: Vec<Vec<u8>> = socket.into_payloads().collect().await;
+    assert_eq!(payloads, vec![vec![1, 2, 3], vec![4], vec![0xFF; 20]]);
+}
+
+#[tokio::test]
+async fn it_sends_packets_until_closed() {
+    let (socket, remote) = AcquiredSocket::pair(23).unwrap();
+
+    let remote = UnixDatagram::from(remote);
+    socket.send(&[1, 2, 3]).await.unwrap();
+    socket.send(&[4]).await.unwrap();
+    let mut buffer = [0; 23];
+    assert_eq!(remote.recv(&mut buffer).unwrap(), 3);
+    assert_eq!(&buffer[..3], &[1, 2, 3]);
+    assert_eq!(remote.recv(&mut buffer).unwrap(), 1);
+    assert_eq!(&buffer[..1], &[4]);
+
+    drop(remote);
+    socket.closed().await;
+    assert!(socket.send(&[5]).await.is_err());
+}
+
+#[tokio::test]
+async fn it_feeds_payloads_to_a_handler() {
+    let (socket, remote) = AcquiredSocket::pair(5).unwrap();
+    let value = Arc::new(Mutex::new(Vec::new()));
+
+    // What a handler of `Event::WriteAcquired` does with its payloads
+    let handler = tokio::spawn(socket.into_payloads().for_each({
+        let value = Arc::clone(&value);
+        move |payload| {
+            *value.lock().unwrap() = payload;
+            future::ready(())
+        }
+    }));
+
+    // Packets longer than the MTU are cut off
+    let remote = UnixDatagram::from(remote);
+    remote.send(&[1, 2]).unwrap();
+    remote.send(&[3, 4, 5, 6, 7, 8]).unwrap();
+    drop(remote);
+
+    handler.await.unwrap();
+    assert_eq!(*value.lock().unwrap(), vec![3, 4, 5, 6, 7]);
+}
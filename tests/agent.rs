@@ -0,0 +1,137 @@
+#![cfg(any(target_os = "linux", target_os = "android"))]
+
+use dbus::{channel::MatchingReceiver, message::MatchRule, nonblock::Proxy, Path};
+use dbus_crossroads::Crossroads;
+use futures::future;
+use std::{
+    io::{BufRead, BufReader},
+    process::{Command, Stdio},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use bluster::{Agent, AgentError, AgentFuture, AgentHandler, Central, IoCapability};
+
+const DEVICE_PATH: &str = "/org/bluez/hci0/dev_AA_BB_CC_DD_EE_FF";
+
+struct FixedPasskey(u32);
+
+impl AgentHandler for FixedPasskey {
+    fn request_passkey(&self, central: Central) -> AgentFuture<u32> {
+        assert_eq!(central.address(), "AA:BB:CC:DD:EE:FF");
+        Box::pin(future::ok(self.0))
+    }
+
+    fn request_authorization(&self, _central: Central) -> AgentFuture<()> {
+        Box::pin(future::err(AgentError::Canceled))
+    }
+}
+
+#[tokio::test]
+async fn it_answers_agent_requests_from_agent_manager() {
+    // A private bus stands in for the system bus, with a mock `org.bluez` on it
+    let mut daemon = match Command::new("dbus-daemon")
+        .args(["--session", "--nofork", "--print-address"])
+        .stdout(Stdio::piped())
+        .spawn()
+    {
+        Ok(daemon) => daemon,
+        Err(err) => {
+            eprintln!("WARNING: failed to start dbus-daemon, skipping: {}", err);
+            return;
+        }
+    };
+    let mut address = String::new();
+    BufReader::new(daemon.stdout.take().unwrap())
+        .read_line(&mut address)
+        .unwrap();
+    std::env::set_var("DBUS_SYSTEM_BUS_ADDRESS", address.trim());
+
+    let (resource, connection) = dbus_tokio::connection::new_system_sync().unwrap();
+    tokio::spawn(async {
+        let err = resource.await;
+        eprintln!("Lost connection to D-Bus: {}", err);
+    });
+    connection
+        .request_name("org.bluez", false, true, false)
+        .await
+        .unwrap();
+
+    let registered = Arc::new(Mutex::new(None));
+    let mut cr = Crossroads::new();
+    let agent_manager = cr.register("org.bluez.AgentManager1", |b| {
+        let registered = Arc::clone(&registered);
+        b.method(
+            "RegisterAgent",
+            ("agent", "capability"),
+            (),
+            move |ctx, _cr, (agent, capability): (Path<'static>, String)| {
+                let sender = ctx.message().sender().unwrap().to_string();
+                registered
+                    .lock()
+                    .unwrap()
+                    .replace((sender, agent, capability));
+                Ok(())
+            },
+        );
+        b.method(
+            "RequestDefaultAgent",
+            ("agent",),
+            (),
+            |_ctx, _cr, (_agent,): (Path<'static>,)| Ok(()),
+        );
+    });
+    cr.insert("/org/bluez", &[agent_manager], ());
+    connection.start_receive(
+        MatchRule::new_method_call(),
+        Box::new(move |msg, conn| {
+            cr.handle_message(msg, conn).unwrap();
+            true
+        }),
+    );
+
+    let agent = Agent::register(IoCapability::KeyboardDisplay, FixedPasskey(123_456))
+        .await
+        .unwrap();
+    let (sender, agent_path, capability) = registered.lock().unwrap().clone().unwrap();
+    assert_eq!(agent_path, agent.object_path);
+    assert_eq!(capability, "KeyboardDisplay");
+
+    let proxy = Proxy::new(
+        sender,
+        agent_path,
+        Duration::from_secs(5),
+        connection.clone(),
+    );
+    let (passkey,): (u32,) = proxy
+        .method_call(
+            "org.bluez.Agent1",
+            "RequestPasskey",
+            (Path::from(DEVICE_PATH),),
+        )
+        .await
+        .unwrap();
+    assert_eq!(passkey, 123_456);
+
+    let err = proxy
+        .method_call::<(), _, _, _>(
+            "org.bluez.Agent1",
+            "RequestConfirmation",
+            (Path::from(DEVICE_PATH), 123_456_u32),
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err.name(), Some("org.bluez.Error.Rejected"));
+
+    let err = proxy
+        .method_call::<(), _, _, _>(
+            "org.bluez.Agent1",
+            "RequestAuthorization",
+            (Path::from(DEVICE_PATH),),
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err.name(), Some("org.bluez.Error.Canceled"));
+
+    daemon.kill().unwrap();
+}
@@ -0,0 +1,83 @@
+use futures::channel::oneshot;
+use tokio::sync::watch;
+use uuid::Uuid;
+
+use bluster::{
+    gatt::{
+        characteristic::{self, Characteristic, ValueChanges},
+        descriptor::{self, Descriptor},
+        event::Subscription,
+        NotificationQueue,
+    },
+    SdpShortUuid,
+};
+
+#[test]
+fn it_keeps_the_attribute_id_across_clones() {
+    let uuid = Uuid::from_sdp_short_uuid(0x2A00_u16);
+    let properties = || characteristic::Properties::new(None, None, None, None);
+    let characteristic = Characteristic::new(uuid, properties(), None, vec![]);
+    let other = Characteristic::new(uuid, properties(), None, vec![]);
+    assert_eq!(characteristic.clone().id(), characteristic.id());
+    assert_ne!(characteristic.id(), other.id());
+
+    let descriptor = Descriptor::new(uuid, descriptor::Properties::new(None, None), None);
+    assert_ne!(descriptor.id(), characteristic.id());
+    assert_eq!(descriptor.clone().id(), descriptor.id());
+}
+
+#[test]
+fn it_keeps_attributes_sharing_a_uuid() {
+    let uuid = Uuid::from_sdp_short_uuid(0x2A4D_u16);
+    let report = || {
+        Characteristic::new(
+            uuid,
+            characteristic::Properties::new(None, None, None, None),
+            None,
+            vec![],
+        )
+    };
+    let first = report();
+    assert_ne!(first, report());
+    assert_eq!(first, first.clone());
+}
+
+#[tokio::test]
+async fn it_shares_the_stored_value_across_clones() {
+    let uuid = Uuid::from_sdp_short_uuid(0x2A26_u16);
+    let properties = characteristic::Properties::new(None, None, None, None);
+    let characteristic = Characteristic::new(uuid, properties, Some(b"1.0".to_vec()), vec![]);
+    let registered = characteristic.clone();
+
+    assert_eq!(characteristic.set_value(b"1.1".to_vec(), false).await, 0);
+    assert_eq!(registered.value(), Some(b"1.1".to_vec()));
+
+    let (_closer, closed) = oneshot::channel();
+    let (subscription, receiver) = Subscription::new(NotificationQueue::default(), closed);
+    registered.subscribers().insert(None, subscription);
+    assert_eq!(characteristic.set_value(b"1.2".to_vec(), true).await, 1);
+    assert_eq!(receiver.recv().await.unwrap().value, b"1.2".to_vec());
+
+    let descriptor = Descriptor::new(uuid, descriptor::Properties::new(None, None), None);
+    descriptor.clone().set_value(vec![1]);
+    assert_eq!(descriptor.value(), Some(vec![1]));
+}
+
+#[tokio::test]
+async fn it_follows_a_watch_channel() {
+    let (sender, receiver) = watch::channel(vec![0]);
+    let characteristic = Characteristic::from_watch(
+        Uuid::from_sdp_short_uuid(0x2A19_u16),
+        receiver,
+        Some(ValueChanges::Notify),
+        vec![],
+    );
+    assert_eq!(characteristic.value(), Some(vec![0]));
+
+    sender.send(vec![42]).unwrap();
+    assert_eq!(characteristic.value(), Some(vec![42]));
+
+    characteristic.set_value(vec![7], false).await;
+    sender.send_replace(vec![43]);
+    assert_eq!(characteristic.value(), Some(vec![7]));
+}
@@ -0,0 +1,55 @@
+use futures::channel::oneshot;
+use std::time::Duration;
+
+use bluster::gatt::{
+    event::{IndicationError, Indicator, Subscription},
+    NotificationQueue,
+};
+
+#[tokio::test]
+async fn it_resolves_on_confirmation() {
+    let (_closer, closed) = oneshot::channel();
+    let (subscription, receiver) = Subscription::new(NotificationQueue::default(), closed);
+    let indicator = Indicator::new(subscription, Duration::from_secs(1));
+
+    tokio::spawn(async move {
+        let (indication, report) = receiver.recv().await.unwrap().into_parts();
+        assert_eq!(indication.value, vec![1, 2, 3]);
+        report.unwrap().send(Ok(())).unwrap();
+        indication.confirmation.send(()).unwrap();
+    });
+    assert_eq!(indicator.indicate(vec![1, 2, 3]).await, Ok(()));
+}
+
+#[tokio::test]
+async fn it_times_out_without_confirmation() {
+    let (_closer, closed) = oneshot::channel();
+    let (subscription, receiver) = Subscription::new(NotificationQueue::default(), closed);
+    let indicator = Indicator::new(subscription, Duration::from_millis(10));
+
+    // The indication is sent, but never confirmed
+    let relay = tokio::spawn(async move {
+        let (indication, report) = receiver.recv().await.unwrap().into_parts();
+        report.unwrap().send(Ok(())).unwrap();
+        indication
+    });
+    assert_eq!(
+        indicator.indicate(vec![1]).await,
+        Err(IndicationError::Timeout)
+    );
+    drop(relay.await.unwrap());
+}
+
+#[tokio::test]
+async fn it_fails_once_closed() {
+    let (closer, closed) = oneshot::channel();
+    let (subscription, _receiver) = Subscription::new(NotificationQueue::default(), closed);
+    let indicator = Indicator::new(subscription, Duration::from_secs(1));
+
+    drop(closer);
+    indicator.closed().await;
+    assert_eq!(
+        indicator.indicate(vec![1]).await,
+        Err(IndicationError::Closed)
+    );
+}
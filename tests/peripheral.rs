@@ -1,6 +1,5 @@
 use futures::{channel::mpsc::channel, prelude::*};
 use std::{
-    collections::HashSet,
     sync::{atomic, Arc, Mutex},
     thread,
     time::Duration,
@@ -13,7 +12,7 @@ use bluster::{
         characteristic::Characteristic,
         descriptor,
         descriptor::Descriptor,
-        event::{Event, Response},
+        event::{Authorization, Event, Response},
         service::Service,
     },
     Peripheral, SdpShortUuid,
@@ -30,8 +29,7 @@ async fn it_advertises_gatt() {
     let (sender_characteristic, receiver_characteristic) = channel(1);
     let (sender_descriptor, receiver_descriptor) = channel(1);
 
-    let mut characteristics: HashSet<Characteristic> = HashSet::new();
-    characteristics.insert(Characteristic::new(
+    let characteristics = vec![Characteristic::new(
         Uuid::from_sdp_short_uuid(0x2A3D as u16),
         characteristic::Properties::new(
             Some(characteristic::Read(characteristic::Secure::Insecure(
@@ -44,23 +42,19 @@ async fn it_advertises_gatt() {
             None,
         ),
         None,
-        {
-            let mut descriptors = HashSet::<Descriptor>::new();
-            descriptors.insert(Descriptor::new(
-                Uuid::from_sdp_short_uuid(0x2A3D as u16),
-                descriptor::Properties::new(
-                    Some(descriptor::Read(descriptor::Secure::Insecure(
-                        sender_descriptor.clone(),
-                    ))),
-                    Some(descriptor::Write(descriptor::Secure::Insecure(
-                        sender_descriptor,
-                    ))),
-                ),
-                None,
-            ));
-            descriptors
-        },
-    ));
+        vec![Descriptor::new(
+            Uuid::from_sdp_short_uuid(0x2A3D as u16),
+            descriptor::Properties::new(
+                Some(descriptor::Read(descriptor::Secure::Insecure(
+                    sender_descriptor.clone(),
+                ))),
+                Some(descriptor::Write(descriptor::Secure::Insecure(
+                    sender_descriptor,
+                ))),
+            ),
+            None,
+        )],
+    )];
 
     let characteristic_handler = async {
         let characteristic_value = Arc::new(Mutex::new(String::from("hi")));
@@ -68,10 +62,16 @@ async fn it_advertises_gatt() {
         let mut rx = receiver_characteristic;
         while let Some(event) = rx.next().await {
             match event {
+                Event::AuthorizeRequest(authorize_request) => {
+                    authorize_request
+                        .response
+                        .send(Authorization::Granted)
+                        .unwrap();
+                }
                 Event::ReadRequest(read_request) => {
                     println!(
-                        "GATT server got a read request with offset {}!",
-                        read_request.offset
+                        "GATT server got a read request for {} with offset {}!",
+                        read_request.attribute, read_request.offset
                     );
                     let value = characteristic_value.lock().unwrap().clone();
                     read_request
@@ -92,6 +92,9 @@ async fn it_advertises_gatt() {
                         .send(Response::Success(vec![]))
                         .unwrap();
                 }
+                Event::WriteAcquired(_) => {
+                    unreachable!("Writes are not acquired by this characteristic!")
+                }
                 Event::NotifySubscribe(notify_subscribe) => {
                     println!("GATT server got a notify subscription!");
                     let notifying = Arc::clone(&notifying);
@@ -113,10 +116,13 @@ async fn it_advertises_gatt() {
                         }
                     });
                 }
-                Event::NotifyUnsubscribe => {
+                Event::NotifyUnsubscribe(_) => {
                     println!("GATT server got a notify unsubscribe!");
                     notifying.store(false, atomic::Ordering::Relaxed);
                 }
+                Event::IndicateSubscribe(_) | Event::IndicateUnsubscribe(_) => {
+                    panic!("Indications are not supported by this characteristic!")
+                }
             };
         }
     };
@@ -158,11 +164,8 @@ async fn it_advertises_gatt() {
     let service_uuid = Uuid::from_sdp_short_uuid(0x1234_u16);
     let peripheral = Peripheral::new().await.unwrap();
     peripheral
-        .add_service(&Service::new(
-            service_uuid,
-            true,
-            characteristics,
-        ))
+        .add_service(&Service::new(service_uuid, true, characteristics))
+        .await
         .unwrap();
     let main_fut = async move {
         while !peripheral.is_powered().await.unwrap() {}
@@ -183,3 +186,28 @@ async fn it_advertises_gatt() {
 
     futures::join!(characteristic_handler, descriptor_handler, main_fut);
 }
+
+#[tokio::test]
+async fn it_registers_changed_services_again() {
+    let peripheral = Peripheral::new().await.unwrap();
+    while !peripheral.is_powered().await.unwrap() {}
+    let primary = Service::new(Uuid::from_sdp_short_uuid(0x1234_u16), true, vec![]);
+    peripheral.add_service(&primary).await.unwrap();
+    peripheral.register_gatt().await.unwrap();
+    let registered_hash = peripheral.database_hash();
+
+    // Services added and removed once registered are registered again right away
+    let feature = Service::new(Uuid::from_sdp_short_uuid(0x1235_u16), true, vec![]);
+    let feature = peripheral.add_service(&feature).await.unwrap();
+    assert_ne!(peripheral.database_hash(), registered_hash);
+    peripheral.remove_service(feature).await.unwrap();
+    assert_eq!(peripheral.database_hash(), registered_hash);
+
+    peripheral.service_changed().await.unwrap();
+    peripheral.unregister_gatt().await.unwrap();
+
+    // Nothing is registered again once unregistered
+    let feature = Service::new(Uuid::from_sdp_short_uuid(0x1236_u16), true, vec![]);
+    peripheral.add_service(&feature).await.unwrap();
+    peripheral.service_changed().await.unwrap();
+}
@@ -0,0 +1,133 @@
+use futures::channel::oneshot;
+
+use bluster::{
+    gatt::{
+        event::{NotificationError, QueueReceiver, Subscription},
+        NotificationQueue, QueuePolicy, Subscribers,
+    },
+    Central,
+};
+
+async fn next<T>(receiver: &QueueReceiver<T>) -> Option<T> {
+    receiver.recv().await.map(|delivery| delivery.value)
+}
+
+#[tokio::test]
+async fn it_closes_on_unsubscribe() {
+    let (closer, closed) = oneshot::channel();
+    let (subscription, receiver) = Subscription::new(NotificationQueue::default(), closed);
+
+    subscription.send(vec![1]).await.unwrap();
+    assert_eq!(next(&receiver).await, Some(vec![1]));
+    assert!(!subscription.is_closed());
+
+    drop(closer);
+    subscription.closed().await;
+    assert!(subscription.is_closed());
+    assert_eq!(
+        subscription.send(vec![2]).await,
+        Err(NotificationError::Closed)
+    );
+    assert_eq!(
+        subscription.try_send(vec![3]),
+        Err(NotificationError::Closed)
+    );
+}
+
+#[tokio::test]
+async fn it_applies_the_queue_policy() {
+    let (_closer, closed) = oneshot::channel();
+    let queue = NotificationQueue::new(2, QueuePolicy::Block);
+    let (subscription, receiver) = Subscription::new(queue, closed);
+    subscription.try_send(1).unwrap();
+    subscription.try_send(2).unwrap();
+    assert_eq!(subscription.try_send(3), Err(NotificationError::Full));
+    assert_eq!(next(&receiver).await, Some(1));
+    subscription.send(3).await.unwrap();
+    assert_eq!(next(&receiver).await, Some(2));
+    assert_eq!(next(&receiver).await, Some(3));
+
+    let (_closer, closed) = oneshot::channel();
+    let queue = NotificationQueue::new(2, QueuePolicy::DropOldest);
+    let (subscription, receiver) = Subscription::new(queue, closed);
+    for value in 1..=3 {
+        subscription.send(value).await.unwrap();
+    }
+    assert_eq!(next(&receiver).await, Some(2));
+    assert_eq!(next(&receiver).await, Some(3));
+
+    let (_closer, closed) = oneshot::channel();
+    let queue = NotificationQueue::new(2, QueuePolicy::DropNewest);
+    let (subscription, receiver) = Subscription::new(queue, closed);
+    subscription.send(1).await.unwrap();
+    subscription.send(2).await.unwrap();
+    assert_eq!(subscription.send(3).await, Err(NotificationError::Dropped));
+    assert_eq!(next(&receiver).await, Some(1));
+    assert_eq!(next(&receiver).await, Some(2));
+
+    let (_closer, closed) = oneshot::channel();
+    let queue = NotificationQueue::new(1, QueuePolicy::Coalesce);
+    let (subscription, receiver) = Subscription::new(queue, closed);
+    for value in 1..=3 {
+        subscription.send(value).await.unwrap();
+    }
+    assert_eq!(next(&receiver).await, Some(3));
+}
+
+#[tokio::test]
+async fn it_reports_delivery() {
+    let (_closer, closed) = oneshot::channel();
+    let queue = NotificationQueue::new(1, QueuePolicy::Coalesce);
+    let (subscription, receiver) = Subscription::new(queue, closed);
+
+    let relay = tokio::spawn(async move {
+        let (value, report) = receiver.recv().await.unwrap().into_parts();
+        report
+            .unwrap()
+            .send(Err(NotificationError::Failed(format!("{:?}", value))))
+            .unwrap();
+    });
+    assert_eq!(
+        subscription.deliver(vec![1]).await,
+        Err(NotificationError::Failed(String::from("[1]")))
+    );
+    relay.await.unwrap();
+    assert_eq!(
+        subscription.deliver(vec![2]).await,
+        Err(NotificationError::Closed)
+    );
+}
+
+#[tokio::test]
+async fn it_fans_out_to_subscribers() {
+    let subscribers = Subscribers::default();
+    let central = Central::new("AA:BB:CC:DD:EE:FF");
+
+    let (anonymous_closer, closed) = oneshot::channel();
+    let (subscription, anonymous) = Subscription::new(NotificationQueue::default(), closed);
+    subscribers.insert(None, subscription);
+    let (_identified_closer, closed) = oneshot::channel();
+    let (subscription, identified) = Subscription::new(NotificationQueue::default(), closed);
+    subscribers.insert(Some(central.clone()), subscription);
+    assert_eq!(subscribers.subscription_count(), 2);
+
+    assert_eq!(subscribers.notify(vec![1]).await, 2);
+    assert_eq!(next(&anonymous).await, Some(vec![1]));
+    assert_eq!(next(&identified).await, Some(vec![1]));
+
+    subscribers
+        .notify_subscriptions_of(&central, vec![2])
+        .await
+        .unwrap();
+    assert_eq!(next(&identified).await, Some(vec![2]));
+    assert_eq!(
+        subscribers
+            .notify_subscriptions_of(&Central::new("00:00:00:00:00:00"), vec![3])
+            .await,
+        Err(NotificationError::Closed)
+    );
+
+    drop(anonymous_closer);
+    drop(anonymous);
+    assert_eq!(subscribers.subscription_count(), 1);
+}
@@ -0,0 +1,121 @@
+use futures::channel::mpsc::channel;
+use uuid::Uuid;
+
+use bluster::{
+    gatt::{
+        characteristic::{self, Characteristic},
+        descriptor::{self, Descriptor},
+        service::Service,
+        validate, Problem,
+    },
+    SdpShortUuid,
+};
+
+#[test]
+fn it_accepts_a_readable_characteristic() {
+    let characteristic = Characteristic::new(
+        Uuid::from_sdp_short_uuid(0x2A00_u16),
+        characteristic::Properties::new(None, None, None, None),
+        Some(b"bluster".to_vec()),
+        vec![],
+    );
+    let service = Service::new(
+        Uuid::from_sdp_short_uuid(0x1800_u16),
+        true,
+        vec![characteristic],
+    );
+    assert!(validate(&service).is_ok());
+}
+
+#[test]
+fn it_lists_every_problem() {
+    let service_uuid = Uuid::from_sdp_short_uuid(0x1234_u16);
+    let empty = Service::new(service_uuid, true, vec![]);
+    assert_eq!(
+        validate(&empty).unwrap_err().problems(),
+        &[Problem::EmptyService {
+            service: service_uuid
+        }]
+    );
+
+    let uuid = Uuid::from_sdp_short_uuid(0x2A37_u16);
+    let cccd = Descriptor::new(
+        Uuid::from_sdp_short_uuid(0x2902_u16),
+        descriptor::Properties::new(None, None),
+        Some(vec![0, 0]),
+    );
+    let characteristic = Characteristic::new(
+        uuid,
+        characteristic::Properties::new(None, None, None, None),
+        None,
+        vec![cccd],
+    )
+    .with_acquire_notify(true);
+    let id = characteristic.id();
+    let service = Service::new(service_uuid, true, vec![characteristic]);
+    assert_eq!(
+        validate(&service).unwrap_err().problems(),
+        &[
+            Problem::InvalidFlags {
+                attribute: id,
+                uuid,
+                reason: "Can't be read, written or subscribed to"
+            },
+            Problem::NotifyWithoutSender {
+                characteristic: id,
+                uuid
+            },
+            Problem::CccdDescriptor {
+                characteristic: id,
+                uuid
+            },
+        ]
+    );
+}
+
+#[test]
+fn it_finds_subscriptions_nobody_receives() {
+    let service_uuid = Uuid::from_sdp_short_uuid(0x1234_u16);
+    let uuid = Uuid::from_sdp_short_uuid(0x2A37_u16);
+    let (notify, receiver) = channel(1);
+    drop(receiver);
+    let notifying = Characteristic::new(
+        uuid,
+        characteristic::Properties::new(None, None, Some(notify), None),
+        None,
+        vec![],
+    );
+    let (indicate, receiver) = channel(1);
+    drop(receiver);
+    let indicating = Characteristic::new(
+        uuid,
+        characteristic::Properties::new(None, None, None, Some(indicate)),
+        None,
+        vec![],
+    );
+    let (notifying_id, indicating_id) = (notifying.id(), indicating.id());
+    let service = Service::new(service_uuid, true, vec![notifying, indicating]);
+    assert_eq!(
+        validate(&service).unwrap_err().problems(),
+        &[
+            Problem::NotifyWithoutSender {
+                characteristic: notifying_id,
+                uuid
+            },
+            Problem::NotifyWithoutSender {
+                characteristic: indicating_id,
+                uuid
+            },
+        ]
+    );
+
+    let (notify, _receiver) = channel(1);
+    let notifying = Characteristic::new(
+        uuid,
+        characteristic::Properties::new(None, None, Some(notify), None),
+        None,
+        vec![],
+    );
+    let service = Service::new(service_uuid, true, vec![notifying]);
+    assert!(validate(&service).is_ok());
+}